@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Storage backends selectable with `data_access`.
+pub const BACKENDS: [&str; 5] = ["redis", "mongo", "cassandra", "mysql", "postgres"];
+
+/// Command-line flags. Each setting falls back to its environment variable when the flag is absent.
+#[derive(Debug, Default, Parser)]
+#[command(about = "Sensor data access demo")]
+pub struct Args {
+    /// TOML file layered over the built-in defaults
+    #[arg(long, env = "CONFIG_FILE")]
+    pub config: Option<PathBuf>,
+    /// Print the effective configuration, then exit
+    #[arg(long)]
+    pub print_config: bool,
+    #[arg(long, env = "DATA_ACCESS")]
+    pub data_access: Option<String>,
+}
+
+/// Every setting of the demo; missing keys in the TOML file keep their defaults.
+///
+/// The stub backends hold no credentials, so there is nothing to redact when printing it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub data_access: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { data_access: "redis".to_string() }
+    }
+}
+
+/// Every problem found while loading the configuration, as `key: message`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join("; "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Defaults, then the TOML file, then environment variables, then flags; validated.
+    pub fn load(args: &Args) -> Result<Config, ConfigError> {
+        let mut config = match &args.config {
+            Some(path) => Config::from_file(path)?,
+            None => Config::default(),
+        };
+        if let Some(data_access) = &args.data_access {
+            config.data_access = data_access.clone();
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError(vec![format!("{}: {}", path.display(), e)]))?;
+        toml::from_str(&text).map_err(|e| ConfigError(vec![format!("{}: {}", path.display(), e)]))
+    }
+
+    /// Check every setting, collecting all violations so they can be reported at once.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+        if !BACKENDS.contains(&self.data_access.as_str()) {
+            errors.push(format!("data_access: must be one of {}", BACKENDS.join(", ")));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError(errors))
+        }
+    }
+
+    /// The configuration as TOML, for `--print-config`.
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("Config is always serializable")
+    }
+}
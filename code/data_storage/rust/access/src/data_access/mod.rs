@@ -7,3 +7,4 @@ pub mod mysql_data_access;
 pub mod postgres_data_access;
 pub mod redis_data_access;
 pub mod sensor_data_access_trait;
+pub mod storage_error;
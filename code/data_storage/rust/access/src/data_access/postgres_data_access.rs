@@ -2,8 +2,7 @@
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
 use crate::data_access::sensor_data_access_trait::SensorDataAccess;
-use std::error::Error;
-use tokio::task;
+use crate::data_access::storage_error::StorageResult;
 
 pub struct PostgresDataAccess;
 
@@ -14,27 +13,25 @@ impl PostgresDataAccess {
 }
 
 impl SensorDataAccess for PostgresDataAccess {
-    fn log_sensor_data<'a>(&'a self, json_data: &'a str) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        let json_owned = json_data.to_string();
-        task::spawn(async move {
-            println!("Logging sensor data to Postgres: {}", json_owned);
-            Ok(())
-        })
+    async fn log_sensor_data(&self, json_data: &str) -> StorageResult<()> {
+        println!("Logging sensor data to Postgres: {}", json_data);
+        Ok(())
     }
 
-    fn fetch_sensor_data(&self) -> task::JoinHandle<Result<Vec<String>, Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
-            println!("Fetching sensor data from Postgres");
-            Ok(vec![
-                r#"{"sensor":"sound","value":75}"#.to_string(),
-            ])
-        })
+    async fn fetch_sensor_data(&self) -> StorageResult<Vec<String>> {
+        println!("Fetching sensor data from Postgres");
+        Ok(vec![
+            r#"{"sensor":"sound","value":75}"#.to_string(),
+        ])
     }
 
-    fn purge_sensor_data(&self) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
-            println!("Purging sensor data from Postgres");
-            Ok(())
-        })
+    async fn ping(&self) -> StorageResult<()> {
+        println!("Pinging Postgres with SELECT 1");
+        Ok(())
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        println!("Purging sensor data from Postgres");
+        Ok(())
     }
 }
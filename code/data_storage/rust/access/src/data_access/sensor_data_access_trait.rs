@@ -1,10 +1,45 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
-use std::error::Error;
+use crate::data_access::storage_error::StorageResult;
+use std::future::Future;
+use std::pin::Pin;
 
+/// A boxed future borrowing the backend, as returned through [`DynSensorDataAccess`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A storage backend; implementations write these methods as plain `async fn`s.
 pub trait SensorDataAccess: Send + Sync {
-    fn log_sensor_data<'a>(&'a self, json_data: &'a str) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>>;
-    fn fetch_sensor_data(&self) -> tokio::task::JoinHandle<Result<Vec<String>, Box<dyn Error + Send + Sync>>>;
-    fn purge_sensor_data(&self) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>>;
+    fn log_sensor_data(&self, json_data: &str) -> impl Future<Output = StorageResult<()>> + Send;
+    fn fetch_sensor_data(&self) -> impl Future<Output = StorageResult<Vec<String>>> + Send;
+    fn purge_sensor_data(&self) -> impl Future<Output = StorageResult<()>> + Send;
+    /// Check the backend answers, with the cheapest round trip it has.
+    fn ping(&self) -> impl Future<Output = StorageResult<()>> + Send;
+}
+
+/// Object-safe view of [`SensorDataAccess`], so the backend chosen at startup can be
+/// held as `Box<dyn DynSensorDataAccess>`. Every backend gets it through the blanket impl.
+pub trait DynSensorDataAccess: Send + Sync {
+    fn log_sensor_data<'a>(&'a self, json_data: &'a str) -> BoxFuture<'a, StorageResult<()>>;
+    fn fetch_sensor_data(&self) -> BoxFuture<'_, StorageResult<Vec<String>>>;
+    fn purge_sensor_data(&self) -> BoxFuture<'_, StorageResult<()>>;
+    fn ping(&self) -> BoxFuture<'_, StorageResult<()>>;
+}
+
+impl<T: SensorDataAccess> DynSensorDataAccess for T {
+    fn log_sensor_data<'a>(&'a self, json_data: &'a str) -> BoxFuture<'a, StorageResult<()>> {
+        Box::pin(SensorDataAccess::log_sensor_data(self, json_data))
+    }
+
+    fn fetch_sensor_data(&self) -> BoxFuture<'_, StorageResult<Vec<String>>> {
+        Box::pin(SensorDataAccess::fetch_sensor_data(self))
+    }
+
+    fn purge_sensor_data(&self) -> BoxFuture<'_, StorageResult<()>> {
+        Box::pin(SensorDataAccess::purge_sensor_data(self))
+    }
+
+    fn ping(&self) -> BoxFuture<'_, StorageResult<()>> {
+        Box::pin(SensorDataAccess::ping(self))
+    }
 }
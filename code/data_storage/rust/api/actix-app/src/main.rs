@@ -1,9 +1,17 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
+mod config;
+mod problem;
+
 use actix_web::{get, App, HttpServer, Responder};
 use actix_web::{post, web, HttpResponse};
 use actix_web::{route, HttpRequest};
+use actix_web::http::header;
+use clap::Parser;
+use config::{Args, Config};
+use problem::{json_config, query_config, Problem};
+use report_writer::{negotiate, record_columns, render_report, Record, ReportFormat};
 
 const SENSOR_DATA: &str = r#"{
     "recorded": 1756655999,
@@ -14,25 +22,10 @@ const SENSOR_DATA: &str = r#"{
     "value": 22.3
 }"#;
 
-fn json_to_csv(json_str: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let json_value: serde_json::Value = serde_json::from_str(json_str)?;
-
-    if let serde_json::Value::Object(map) = json_value {
-        let mut csv_string = String::new();
-
-        // Write the header row using the keys
-        let headers: Vec<&str> = map.keys().map(|k| k.as_str()).collect();
-        csv_string.push_str(&headers.join(","));
-        csv_string.push('\n');
-
-        // Write the values row
-        let values: Vec<String> = map.values().map(|v| v.to_string()).collect();
-        csv_string.push_str(&values.join(","));
-        csv_string.push('\n');
-
-        Ok(csv_string)
-    } else {
-        Err("Input JSON must be an object".into())
+fn sensor_records() -> Result<Vec<Record>, Box<dyn std::error::Error>> {
+    match serde_json::from_str(SENSOR_DATA)? {
+        serde_json::Value::Object(map) => Ok(vec![map]),
+        _ => Err("Input JSON must be an object".into()),
     }
 }
 
@@ -44,6 +37,23 @@ async fn hello() -> impl Responder {
     }))
 }
 
+// This function is the liveness check: the process is up and serving.
+#[get("/healthz")]
+async fn healthz() -> impl Responder {
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": "ok"
+    }))
+}
+
+// This function is the readiness check: this service has no backend to wait on, so it is ready as soon as it serves.
+#[get("/readyz")]
+async fn readyz() -> impl Responder {
+    HttpResponse::Ok().json(serde_json::json!({
+        "ready": true,
+        "stores": []
+    }))
+}
+
 // This function is the handler for POST requests on the "/echo" path.
 #[post("/echo")]
 async fn echo(req_body: web::Json<serde_json::Value>) -> HttpResponse {
@@ -57,28 +67,35 @@ async fn log(req_body: web::Json<serde_json::Value>) -> HttpResponse {
 }
 
 // This function is the handler for GET requests on the root path "/report".
+// The format comes from the optional `format` query parameter, else the `Accept` header.
 #[get("/report")]
-async fn report() -> impl Responder {
-    let cvs_string = match json_to_csv(SENSOR_DATA) {
-        Ok(csv) => csv,
-        Err(err) => {
-            return HttpResponse::InternalServerError()
-                .body(format!("Failed to generate CSV: {}", err));
-        }
-    };
-    HttpResponse::Ok()
-        .content_type("text/csv")
-        .body(cvs_string)
+async fn report(req: HttpRequest, format: web::Query<ReportFormat>) -> Result<HttpResponse, Problem> {
+    let accept = req.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let writer = negotiate(format.format.as_deref(), accept)?;
+    let records = sensor_records().map_err(|err| {
+        eprintln!("Error parsing sensor data: {}", err);
+        Problem::internal("Failed to generate report")
+    })?;
+    let mut response = HttpResponse::Ok();
+    response.content_type(writer.content_type());
+    response.insert_header((header::VARY, "Accept"));
+    if writer.attachment() {
+        response.insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"report.{}\"", writer.name()),
+        ));
+    }
+    Ok(response.body(render_report(writer, &record_columns(&records), &records)))
 }
 
 // This function handles purge GET and POST requests on the "/purge" path.
 #[route("/purge", method = "GET", method = "POST")]
 async fn purge(req: HttpRequest, _body: web::Bytes) -> impl Responder {
-    match req.method() {
-        &actix_web::http::Method::GET => HttpResponse::Ok().json(serde_json::json!({
+    match *req.method() {
+        actix_web::http::Method::GET => HttpResponse::Ok().json(serde_json::json!({
             "message": "purged"
         })),
-        &actix_web::http::Method::POST => HttpResponse::Ok().json(serde_json::json!({
+        actix_web::http::Method::POST => HttpResponse::Ok().json(serde_json::json!({
             "message": "purged"
         })),
         _ => HttpResponse::MethodNotAllowed().finish(),
@@ -89,17 +106,31 @@ async fn purge(req: HttpRequest, _body: web::Bytes) -> impl Responder {
 // The #[actix_web::main] macro sets up an async runtime for your main function.
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let config = Config::load(&args).unwrap_or_else(|e| {
+        eprintln!("Invalid configuration: {}", e);
+        std::process::exit(2);
+    });
+    if args.print_config {
+        print!("{}", config.to_toml());
+        return Ok(());
+    }
+
     // Create a new HttpServer.
     HttpServer::new(|| {
         // Create a new App instance and register the `hello` service.
-        App::new().service(hello)
+        App::new().app_data(json_config())
+            .app_data(query_config())
+            .service(hello)
+            .service(healthz)
+            .service(readyz)
             .service(echo)
             .service(log)
             .service(report)
             .service(purge)
     })
-    // Bind the server to the local address "127.0.0.1" and port 8080.
-    .bind(("0.0.0.0", 8080))?
+    // Bind the server to the configured address.
+    .bind(config.bind.as_str())?
     // Start the server and wait for it to complete.
     .run()
     .await
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use report_writer::NegotiationError;
+use actix_web::error::{JsonPayloadError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt;
+
+/// Largest request body accepted, the same in the axum and actix apps.
+pub const BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// An RFC 9457 problem details body, served as `application/problem+json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+}
+
+impl Problem {
+    /// A problem whose title is the status code's reason phrase, as `about:blank` requires.
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Problem {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Unknown Status"),
+            status: status.as_u16(),
+            detail: detail.into(),
+        }
+    }
+
+    pub fn bad_request(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::BAD_REQUEST, detail)
+    }
+
+    pub fn internal(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::INTERNAL_SERVER_ERROR, detail)
+    }
+
+    pub fn invalid_json(e: serde_json::Error) -> Self {
+        Problem::bad_request(format!("Invalid JSON: {}", e))
+    }
+
+    pub fn payload_too_large() -> Self {
+        Problem::new(StatusCode::PAYLOAD_TOO_LARGE, format!("Request body is larger than {} bytes", BODY_LIMIT))
+    }
+}
+
+impl From<NegotiationError> for Problem {
+    fn from(e: NegotiationError) -> Self {
+        let status = match e {
+            NegotiationError::UnknownFormat(_) => StatusCode::BAD_REQUEST,
+            NegotiationError::NotAcceptable => StatusCode::NOT_ACCEPTABLE,
+        };
+        Problem::new(status, e.to_string())
+    }
+}
+
+/// Body errors of `web::Json`, worded like the axum app's so both serve the same problem.
+impl From<JsonPayloadError> for Problem {
+    fn from(e: JsonPayloadError) -> Self {
+        match e {
+            JsonPayloadError::OverflowKnownLength { .. } | JsonPayloadError::Overflow { .. } => Problem::payload_too_large(),
+            JsonPayloadError::Deserialize(e) => Problem::invalid_json(e),
+            e => Problem::bad_request(e.to_string()),
+        }
+    }
+}
+
+impl From<QueryPayloadError> for Problem {
+    fn from(e: QueryPayloadError) -> Self {
+        match e {
+            QueryPayloadError::Deserialize(e) => Problem::bad_request(format!("Failed to deserialize query string: {}", e)),
+            e => Problem::bad_request(e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.detail)
+    }
+}
+
+impl ResponseError for Problem {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .content_type(PROBLEM_JSON)
+            .body(serde_json::to_vec(self).expect("Problem is always serializable"))
+    }
+}
+
+/// Serve malformed JSON bodies as problems instead of actix's plain-text errors.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(BODY_LIMIT)
+        .content_type_required(false)
+        .error_handler(|e, _req| Problem::from(e).into())
+}
+
+/// Serve malformed query strings as problems instead of actix's plain-text errors.
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|e, _req| Problem::from(e).into())
+}
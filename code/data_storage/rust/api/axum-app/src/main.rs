@@ -1,10 +1,17 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
-use axum::{Router, body::Bytes, response::IntoResponse};
+mod config;
+mod problem;
+
+use axum::{Router, body::Bytes, extract::{DefaultBodyLimit, Query}, response::IntoResponse};
+use axum::extract::rejection::{BytesRejection, QueryRejection};
 use axum::response::Response;
-use axum::http::{header, HeaderValue, StatusCode};
-use std::net::SocketAddr;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use clap::Parser;
+use config::{Args, Config};
+use problem::{Problem, BODY_LIMIT};
+use report_writer::{negotiate, record_columns, render_report, Record, ReportFormat};
 use tokio::net::TcpListener;
 
 const SENSOR_DATA: &str = r#"{
@@ -16,44 +23,41 @@ const SENSOR_DATA: &str = r#"{
     "value": 22.3
 }"#;
 
-fn json_to_csv(json_str: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let json_value: serde_json::Value = serde_json::from_str(json_str)?;
-
-    if let serde_json::Value::Object(map) = json_value {
-        let mut csv_string = String::new();
-
-        // Write the header row using the keys
-        let headers: Vec<&str> = map.keys().map(|k| k.as_str()).collect();
-        csv_string.push_str(&headers.join(","));
-        csv_string.push('\n');
-
-        // Write the values row
-        let values: Vec<String> = map.values().map(|v| v.to_string()).collect();
-        csv_string.push_str(&values.join(","));
-        csv_string.push('\n');
-
-        Ok(csv_string)
-    } else {
-        Err("Input JSON must be an object".into())
+fn sensor_records() -> Result<Vec<Record>, Box<dyn std::error::Error>> {
+    match serde_json::from_str(SENSOR_DATA)? {
+        serde_json::Value::Object(map) => Ok(vec![map]),
+        _ => Err("Input JSON must be an object".into()),
     }
 }
 
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+    let config = Config::load(&args).unwrap_or_else(|e| {
+        eprintln!("Invalid configuration: {}", e);
+        std::process::exit(2);
+    });
+    if args.print_config {
+        print!("{}", config.to_toml());
+        return;
+    }
+
     // Build our application with the external handler function
     let app = Router::new()
         .route("/", axum::routing::get(root_handler))
+        .route("/healthz", axum::routing::get(healthz_handler))
+        .route("/readyz", axum::routing::get(readyz_handler))
         .route("/echo", axum::routing::post(echo_handler))
         .route("/log", axum::routing::post(log_handler))
         .route("/report", axum::routing::get(report_handler))
         .route("/purge", axum::routing::post(purge_handler))
-        .route("/purge", axum::routing::get(purge_handler));
+        .route("/purge", axum::routing::get(purge_handler))
+        .layer(DefaultBodyLimit::max(BODY_LIMIT));
 
-    // Listen on a specified address
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
-    let listener = TcpListener::bind(&addr).await.unwrap();
+    // Listen on the configured address
+    let listener = TcpListener::bind(&config.bind).await.unwrap();
 
-    println!("Listening on http://{}", addr);
+    println!("Listening on http://{}", config.bind);
 
     // Serve the application
     axum::serve(listener, app).await.unwrap();
@@ -64,47 +68,49 @@ pub async fn root_handler() -> impl IntoResponse {
     axum::response::Json(serde_json::json!({"message": "axum api server is running"}))
 }
 
-pub async fn echo_handler(body: Bytes) -> impl IntoResponse {
-    let json_result = serde_json::from_slice::<serde_json::Value>(&body);
-    if let Ok(json) = &json_result {
-        println!("{}", json);
-    }
-    match json_result {
-        Ok(json) => axum::response::Json(json),
-        Err(_) => axum::response::Json(serde_json::json!({"error": "Invalid JSON"})),
-    }
+// Liveness: the process is up and serving
+pub async fn healthz_handler() -> impl IntoResponse {
+    axum::response::Json(serde_json::json!({"status": "ok"}))
 }
 
-pub async fn log_handler(body: Bytes) -> impl IntoResponse {
-    let json_result = serde_json::from_slice::<serde_json::Value>(&body);
-    if let Ok(json) = &json_result {
-        println!("{}", json);
-    }
-    match json_result {
-        Ok(json) => axum::response::Json(json),
-        Err(_) => axum::response::Json(serde_json::json!({"error": "Invalid JSON"})),
-    }
+// Readiness: this service has no backend to wait on, so it is ready as soon as it serves
+pub async fn readyz_handler() -> impl IntoResponse {
+    axum::response::Json(serde_json::json!({"ready": true, "stores": []}))
+}
+
+pub async fn echo_handler(body: Result<Bytes, BytesRejection>) -> Result<impl IntoResponse, Problem> {
+    let json = serde_json::from_slice::<serde_json::Value>(&body?).map_err(Problem::invalid_json)?;
+    println!("{}", json);
+    Ok(axum::response::Json(json))
 }
 
-pub async fn report_handler() -> impl IntoResponse {
-    let csv_data = match json_to_csv(SENSOR_DATA) {
-        Ok(data) => data,
-        Err(err) => {
-            eprintln!("Error converting JSON to CSV: {}", err);
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to generate report").into_response();
-        }
-    };
-    let response: Response<axum::body::Body> = Response::builder()
+pub async fn log_handler(body: Result<Bytes, BytesRejection>) -> Result<impl IntoResponse, Problem> {
+    let json = serde_json::from_slice::<serde_json::Value>(&body?).map_err(Problem::invalid_json)?;
+    println!("{}", json);
+    Ok(axum::response::Json(json))
+}
+
+pub async fn report_handler(headers: HeaderMap, format: Result<Query<ReportFormat>, QueryRejection>) -> Result<Response, Problem> {
+    let Query(format) = format?;
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let writer = negotiate(format.format.as_deref(), accept)?;
+    let records = sensor_records().map_err(|err| {
+        eprintln!("Error parsing sensor data: {}", err);
+        Problem::internal("Failed to generate report")
+    })?;
+    let mut builder = Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "text/csv")
-        .header(
+        .header(header::CONTENT_TYPE, writer.content_type())
+        .header(header::VARY, "Accept");
+    if writer.attachment() {
+        builder = builder.header(
             header::CONTENT_DISPOSITION,
-            HeaderValue::from_str("attachment; filename=\"report.csv\"").unwrap(),
-        )
-        .body(axum::body::Body::from(csv_data))
-        .unwrap();
-
-    response.into_response()
+            HeaderValue::from_str(&format!("attachment; filename=\"report.{}\"", writer.name())).unwrap(),
+        );
+    }
+    Ok(builder
+        .body(axum::body::Body::from(render_report(writer, &record_columns(&records), &records)))
+        .unwrap())
 }
 
 pub async fn purge_handler() -> impl IntoResponse {
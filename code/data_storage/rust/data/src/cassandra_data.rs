@@ -9,16 +9,12 @@ use cdrs_tokio::cluster::TcpConnectionManager;
 use cdrs_tokio::query_values;
 use cdrs_tokio::types::IntoRustByName;
 use log::info;
-use crate::sensor_data_json_helper::validate_sensor_json;
+use crate::config::CassandraConfig;
+use crate::sensor_reading::SensorReading;
 
-const CASSANDRA_SERVER_IP: &str = "localhost";
-const CASSANDRA_SERVER_PORT: u16 = 9042;
-const KEYSPACE_NAME: &str = "sensor_data_db";
-const TABLE_NAME: &str = "sensor_data";
-
-pub async fn get_session() -> Result<Session<TransportTcp, TcpConnectionManager, RoundRobinLoadBalancingStrategy<TransportTcp, TcpConnectionManager>>, String> {
+pub async fn get_session(config: &CassandraConfig) -> Result<Session<TransportTcp, TcpConnectionManager, RoundRobinLoadBalancingStrategy<TransportTcp, TcpConnectionManager>>, String> {
     let cluster_config = NodeTcpConfigBuilder::new()
-        .with_contact_point(format!("{}:{}", CASSANDRA_SERVER_IP, CASSANDRA_SERVER_PORT).into())
+        .with_contact_point(format!("{}:{}", config.host, config.port).into())
         .build()
         .await
         .map_err(|e| format!("Cluster config error: {}", e))?;
@@ -32,73 +28,68 @@ pub async fn get_session() -> Result<Session<TransportTcp, TcpConnectionManager,
     Ok(session)
 }
 
-pub async fn create_keyspace_and_table() -> Result<(), String> {
-    let session = get_session().await?;
+pub async fn create_keyspace_and_table(config: &CassandraConfig) -> Result<(), String> {
+    let session = get_session(config).await?;
     let create_ks = format!(
         "CREATE KEYSPACE IF NOT EXISTS {} WITH REPLICATION = {{ 'class' : 'SimpleStrategy', 'replication_factor' : 1 }};",
-        KEYSPACE_NAME
+        config.keyspace
     );
     session.query(create_ks).await.map_err(|e| format!("Keyspace create error: {}", e))?;
 
-    let use_ks = format!("USE {};", KEYSPACE_NAME);
+    let use_ks = format!("USE {};", config.keyspace);
     session.query(use_ks).await.map_err(|e| format!("Use keyspace error: {}", e))?;
 
     let create_table = format!(
         "CREATE TABLE IF NOT EXISTS {} (location TEXT, recorded BIGINT, sensor TEXT, measurement TEXT, units TEXT, value DOUBLE, PRIMARY KEY ((location), recorded, sensor));",
-        TABLE_NAME
+        config.table
     );
     session.query(create_table).await.map_err(|e| format!("Table create error: {}", e))?;
     info!("Keyspace and table created successfully.");
     Ok(())
 }
 
-pub async fn insert_json_row(json_data: &str) -> Result<(), String> {
-    let parsed = validate_sensor_json(json_data)?;
-    let location = parsed["location"].as_str().unwrap();
-    let recorded = parsed["recorded"].as_i64().unwrap();
-    let sensor = parsed["sensor"].as_str().unwrap();
-    let measurement = parsed["measurement"].as_str().unwrap();
-    let units = parsed["units"].as_str().unwrap();
-    let value = parsed["value"].as_f64().unwrap();
+pub async fn insert_json_row(config: &CassandraConfig, json_data: &str) -> Result<(), String> {
+    let reading = SensorReading::from_json(json_data).map_err(|e| e.to_string())?;
 
-    let session = get_session().await?;
+    let session = get_session(config).await?;
     let insert_query = format!(
         "INSERT INTO {}.{} (location, recorded, sensor, measurement, units, value) VALUES (?, ?, ?, ?, ?, ?);",
-        KEYSPACE_NAME, TABLE_NAME
+        config.keyspace, config.table
     );
     let prepared = session.prepare(&insert_query).await.map_err(|e| format!("Prepared statement error: {}", e))?;
-    let values = query_values!(location, recorded, sensor, measurement, units, value);
+    let values = query_values!(reading.location, reading.recorded, reading.sensor, reading.measurement, reading.units, reading.value);
     session.exec_with_values(&prepared, values).await.map_err(|e| format!("Query execution error: {}", e))?;
     info!("Data inserted successfully.");
     Ok(())
 }
 
-pub async fn fetch_table_as_csv() -> Result<String, String> {
-    let session = get_session().await?;
+pub async fn fetch_table_readings(config: &CassandraConfig) -> Result<Vec<SensorReading>, String> {
+    let session = get_session(config).await?;
     let select_query = format!(
         "SELECT location, recorded, sensor, measurement, units, value FROM {}.{};",
-        KEYSPACE_NAME, TABLE_NAME
+        config.keyspace, config.table
     );
     let response = session.query(select_query).await.map_err(|e| format!("Query execution error: {}", e))?;
     let rows = response.response_body().map_err(|e| format!("Response body error: {}", e))?.into_rows().ok_or("No rows found")?;
-    let mut csv_data = String::from("location,recorded,sensor,measurement,units,value\n");
+    let mut readings = Vec::new();
     for row in rows {
-        let location: String = row.get_by_name("location").unwrap().unwrap();
-        let recorded: i64 = row.get_by_name("recorded").unwrap().unwrap();
-        let sensor: String = row.get_by_name("sensor").unwrap().unwrap();
-        let measurement: String = row.get_by_name("measurement").unwrap().unwrap();
-        let units: String = row.get_by_name("units").unwrap().unwrap();
-        let value: f64 = row.get_by_name("value").unwrap().unwrap();
-        csv_data.push_str(&format!("{},{},{},{},{},{}\n", location, recorded, sensor, measurement, units, value));
+        readings.push(SensorReading {
+            recorded: row.get_by_name("recorded").unwrap().unwrap(),
+            location: row.get_by_name("location").unwrap().unwrap(),
+            sensor: row.get_by_name("sensor").unwrap().unwrap(),
+            measurement: row.get_by_name("measurement").unwrap().unwrap(),
+            units: row.get_by_name("units").unwrap().unwrap(),
+            value: row.get_by_name("value").unwrap().unwrap(),
+        });
     }
-    Ok(csv_data)
+    Ok(readings)
 }
 
-pub async fn delete_row(location: &str, recorded: i64, sensor: &str) -> Result<(), String> {
-    let session = get_session().await?;
+pub async fn delete_row(config: &CassandraConfig, location: &str, recorded: i64, sensor: &str) -> Result<(), String> {
+    let session = get_session(config).await?;
     let delete_query = format!(
         "DELETE FROM {}.{} WHERE location = ? AND recorded = ? AND sensor = ?;",
-        KEYSPACE_NAME, TABLE_NAME
+        config.keyspace, config.table
     );
     let prepared = session.prepare(&delete_query).await.map_err(|e| format!("Prepared statement error: {}", e))?;
     let values = query_values!(location, recorded, sensor);
@@ -2,37 +2,143 @@
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
 mod cassandra_data;
+mod config;
 mod mongo_data;
 mod mysql_data;
 mod postgres_data;
 mod redis_data;
+mod sensor_data_arrow;
+mod sensor_data_csv;
 mod sensor_data_json_helper;
+mod sensor_reading;
 
+use clap::Parser;
+use config::{Args, Command, Config};
+use sensor_store::cassandra_data_access::CassandraDataAccess;
+use sensor_store::fanout_data_access::NamedStore;
+use sensor_store::jsonl_data_access::JsonlDataAccess;
+use sensor_store::memory_data_access::MemoryDataAccess;
+use sensor_store::migrations::migrate;
+use sensor_store::mongo_data_access::MongoDataAccess;
+use sensor_store::mysql_data_access::MySQLDataAccess;
+use sensor_store::postgres_data_access::PostgresDataAccess;
+use sensor_store::redis_data_access::RedisDataAccess;
+use sensor_store::retention::RetentionPolicy;
+use sensor_store::sensor_data_access_trait::DynSensorDataAccess;
+use sensor_store::sensor_data_copy::run_copy;
+use sensor_store::sqlite_data_access::SqliteDataAccess;
+use sensor_store::storage_error::StorageResult;
+use std::path::Path;
+use std::sync::Arc;
+use sensor_data_arrow::{write_export, ExportFormat};
+use sensor_data_csv::readings_to_csv;
 use sensor_data_json_helper::create_sample_json;
+use sensor_reading::SensorReading;
 use log::{info, error};
 
+/// Log a backend's readings as CSV and, when `export_dir` is set, also write them
+/// there as `<name>.parquet` and `<name>.arrow` for loading into notebooks.
+fn report_readings(config: &Config, label: &str, name: &str, readings: &[SensorReading]) {
+    info!("{} CSV:\n{}", label, readings_to_csv(readings));
+    let Some(dir) = &config.export_dir else {
+        return;
+    };
+    for format in ExportFormat::ALL {
+        match write_export(dir, name, format, readings) {
+            Ok(path) => info!("{} exported to {}", label, path.display()),
+            Err(e) => error!("{} export error: {}", label, e),
+        }
+    }
+}
+
+/// Connect one of the `SensorDataAccess` backends by its name in `config::BACKENDS`.
+async fn connect_store(name: &str, config: &Config, retention: &RetentionPolicy) -> StorageResult<NamedStore> {
+    let store: Arc<dyn DynSensorDataAccess> = match name {
+        "redis" => Arc::new(RedisDataAccess::connect(&config.redis).await?),
+        "mongo" => Arc::new(MongoDataAccess::connect(&config.mongo, retention).await?),
+        "cassandra" => Arc::new(CassandraDataAccess::connect(&config.cassandra, retention).await?),
+        "mysql" => Arc::new(MySQLDataAccess::connect(&config.mysql).await?),
+        "postgres" => Arc::new(PostgresDataAccess::connect(&config.postgres).await?),
+        "sqlite" => Arc::new(SqliteDataAccess::connect(&config.sqlite).await?),
+        "jsonl" => Arc::new(JsonlDataAccess::connect(&config.jsonl).await?),
+        "memory" => Arc::new(MemoryDataAccess::new()),
+        _ => unreachable!("clap only accepts names in BACKENDS"),
+    };
+    Ok((name.to_string(), store))
+}
+
+/// The `copy` command: stream every reading of one backend into another through
+/// `SensorDataAccess`, then compare both. Returns the process exit code.
+async fn copy(config: &Config, from: &str, to: &str, batch_size: usize, checkpoint: Option<&Path>, dry_run: bool) -> i32 {
+    if from == to {
+        error!("Copy needs two different backends");
+        return 2;
+    }
+    let retention = RetentionPolicy::from_config(&config.retention);
+    let mut stores = Vec::new();
+    for name in [from, to] {
+        match connect_store(name, config, &retention).await {
+            Ok(store) => stores.push(store),
+            Err(e) => {
+                error!("Failed to connect to {}: {}", name, e);
+                return 1;
+            }
+        }
+    }
+    // The source is read and the destination written at their current schema
+    if !dry_run {
+        for (name, store) in &stores {
+            if let Err(e) = migrate(&**store).await {
+                error!("Migration failed for {}: {}", name, e);
+                return 1;
+            }
+        }
+    }
+    match run_copy(&stores[0], &stores[1], batch_size, checkpoint, dry_run).await {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            error!("Copy failed: {}", e);
+            1
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
+    let args = Args::parse();
+    let config = Config::load(&args).unwrap_or_else(|e| {
+        eprintln!("Invalid configuration: {}", e);
+        std::process::exit(2);
+    });
+    if args.print_config {
+        print!("{}", config.to_redacted_toml());
+        return;
+    }
+    if let Some(Command::Copy { from, to, batch_size, checkpoint, dry_run }) = &args.command {
+        std::process::exit(copy(&config, from, to, batch_size.get(), checkpoint.as_deref(), *dry_run).await);
+    }
+
     // --- Cassandra ---
     info!("--- Cassandra Workflow ---");
-    if let Err(e) = cassandra_data::create_keyspace_and_table().await {
+    if let Err(e) = cassandra_data::create_keyspace_and_table(&config.cassandra).await {
         error!("Cassandra setup error: {}", e);
     } else {
         info!("Cassandra keyspace and table created successfully.");
     }
     let cass_json = create_sample_json();
-    if let Err(e) = cassandra_data::insert_json_row(&cass_json).await {
+    if let Err(e) = cassandra_data::insert_json_row(&config.cassandra, &cass_json).await {
         error!("Cassandra insert error: {}", e);
     } else {
         info!("Cassandra row inserted successfully.");
     }
-    match cassandra_data::fetch_table_as_csv().await {
-        Ok(csv) => info!("Cassandra CSV:\n{}", csv),
+    match cassandra_data::fetch_table_readings(&config.cassandra).await {
+        Ok(readings) => report_readings(&config, "Cassandra", "cassandra", &readings),
         Err(e) => error!("Cassandra fetch error: {}", e),
     }
-    if let Err(e) = cassandra_data::delete_row("den", 1756655999, "bmp280").await {
+    if let Err(e) = cassandra_data::delete_row(&config.cassandra, "den", 1756655999, "bmp280").await {
         error!("Cassandra delete error: {}", e);
     } else {
         info!("Cassandra row deleted successfully.");
@@ -41,16 +147,16 @@ async fn main() {
     // --- MongoDB ---
     info!("--- MongoDB Workflow ---");
     let mongo_json = create_sample_json();
-    if let Err(e) = mongo_data::insert_json_into_collection(&mongo_json).await {
+    if let Err(e) = mongo_data::insert_json_into_collection(&config.mongo, &mongo_json).await {
         error!("MongoDB insert error: {}", e);
     } else {
         info!("MongoDB document inserted successfully.");
     }
-    match mongo_data::fetch_collection_as_csv().await {
-        Ok(csv) => info!("MongoDB CSV:\n{}", csv),
+    match mongo_data::fetch_collection_readings(&config.mongo).await {
+        Ok(readings) => report_readings(&config, "MongoDB", "mongo", &readings),
         Err(e) => error!("MongoDB fetch error: {}", e),
     }
-    if let Err(e) = mongo_data::delete_row(1756655999, "den", "bmp280").await {
+    if let Err(e) = mongo_data::delete_row(&config.mongo, 1756655999, "den", "bmp280").await {
         error!("MongoDB delete error: {}", e);
     } else {
         info!("MongoDB document deleted successfully.");
@@ -58,22 +164,22 @@ async fn main() {
 
     // --- MySQL ---
     info!("--- MySQL Workflow ---");
-    if let Err(e) = mysql_data::setup_database().await {
+    if let Err(e) = mysql_data::setup_database(&config.mysql).await {
         error!("MySQL setup error: {}", e);
     } else {
         info!("MySQL database setup successfully.");
     }
     let mysql_json = create_sample_json();
-    if let Err(e) = mysql_data::insert_json_into_table(&mysql_json).await {
+    if let Err(e) = mysql_data::insert_json_into_table(&config.mysql, &mysql_json).await {
         error!("MySQL insert error: {}", e);
     } else {
         info!("MySQL row inserted successfully.");
     }
-    match mysql_data::fetch_data_as_csv().await {
-        Ok(csv) => info!("MySQL CSV:\n{}", csv),
+    match mysql_data::fetch_readings(&config.mysql).await {
+        Ok(readings) => report_readings(&config, "MySQL", "mysql", &readings),
         Err(e) => error!("MySQL fetch error: {}", e),
     }
-    if let Err(e) = mysql_data::delete_row(1756655999, "den", "bmp280").await {
+    if let Err(e) = mysql_data::delete_row(&config.mysql, 1756655999, "den", "bmp280").await {
         error!("MySQL delete error: {}", e);
     } else {
         info!("MySQL row deleted successfully.");
@@ -81,22 +187,22 @@ async fn main() {
 
     // --- PostgreSQL ---
     info!("--- PostgreSQL Workflow ---");
-    if let Err(e) = postgres_data::setup_database().await {
+    if let Err(e) = postgres_data::setup_database(&config.postgres).await {
         error!("Postgres setup error: {}", e);
     } else {
         info!("PostgreSQL database setup successfully.");
     }
     let pg_json = create_sample_json();
-    if let Err(e) = postgres_data::insert_json_into_table(&pg_json).await {
+    if let Err(e) = postgres_data::insert_json_into_table(&config.postgres, &pg_json).await {
         error!("Postgres insert error: {}", e);
     } else {
         info!("PostgreSQL row inserted successfully.");
     }
-    match postgres_data::fetch_data_as_csv().await {
-        Ok(csv) => info!("Postgres CSV:\n{}", csv),
+    match postgres_data::fetch_readings(&config.postgres).await {
+        Ok(readings) => report_readings(&config, "Postgres", "postgres", &readings),
         Err(e) => error!("Postgres fetch error: {}", e),
     }
-    if let Err(e) = postgres_data::delete_row(1756655999, "den", "bmp280").await {
+    if let Err(e) = postgres_data::delete_row(&config.postgres, 1756655999, "den", "bmp280").await {
         error!("Postgres delete error: {}", e);
     } else {
         info!("PostgreSQL row deleted successfully.");
@@ -106,16 +212,16 @@ async fn main() {
     info!("--- Redis Workflow ---");
     let redis_key = "sensor:bmp280:single";
     let redis_json = create_sample_json();
-    if let Err(e) = redis_data::set_json(redis_key, &redis_json).await {
+    if let Err(e) = redis_data::set_json(&config.redis, redis_key, &redis_json).await {
         error!("Redis SET error: {}", e);
     } else {
         info!("Redis key-value pair set successfully.");
     }
-    match redis_data::get_json(redis_key).await {
+    match redis_data::get_json(&config.redis, redis_key).await {
         Ok(val) => info!("Redis GET value: {}", val),
         Err(e) => error!("Redis GET error: {}", e),
     }
-    if let Err(e) = redis_data::delete_key(redis_key).await {
+    if let Err(e) = redis_data::delete_key(&config.redis, redis_key).await {
         error!("Redis DEL error: {}", e);
     } else {
         info!("Redis key deleted successfully.");
@@ -124,20 +230,20 @@ async fn main() {
     let list_key = "sensor:bmp280:list";
     for _ in 0..3 {
         let json = create_sample_json();
-        if let Err(e) = redis_data::lpush_json(list_key, &json).await {
+        if let Err(e) = redis_data::lpush_json(&config.redis, list_key, &json).await {
             error!("Redis LPUSH error: {}", e);
         } else {
             info!("Redis LPUSH executed successfully.");
         }
     }
-    match redis_data::lrange_json(list_key).await {
-        Ok(values) => match redis_data::json_array_to_csv(&values) {
-            Ok(csv) => info!("Redis List as CSV:\n{}", csv),
-            Err(e) => error!("Redis List CSV error: {}", e),
+    match redis_data::lrange_json(&config.redis, list_key).await {
+        Ok(values) => match redis_data::json_array_to_readings(&values) {
+            Ok(readings) => report_readings(&config, "Redis List", "redis_list", &readings),
+            Err(e) => error!("Redis List parse error: {}", e),
         },
         Err(e) => error!("Redis LRANGE error: {}", e),
     }
-    if let Err(e) = redis_data::delete_key(list_key).await {
+    if let Err(e) = redis_data::delete_key(&config.redis, list_key).await {
         error!("Redis DEL error: {}", e);
     } else {
         info!("Redis list key deleted successfully.");
@@ -146,20 +252,20 @@ async fn main() {
     let set_key = "sensor:bmp280:set";
     for _ in 0..3 {
         let json = create_sample_json();
-        if let Err(e) = redis_data::sadd_json(set_key, &json).await {
+        if let Err(e) = redis_data::sadd_json(&config.redis, set_key, &json).await {
             error!("Redis SADD error: {}", e);
         } else {
             info!("Redis SADD executed successfully.");
         }
     }
-    match redis_data::smembers_json(set_key).await {
-        Ok(values) => match redis_data::json_array_to_csv(&values) {
-            Ok(csv) => info!("Redis Set as CSV:\n{}", csv),
-            Err(e) => error!("Redis Set CSV error: {}", e),
+    match redis_data::smembers_json(&config.redis, set_key).await {
+        Ok(values) => match redis_data::json_array_to_readings(&values) {
+            Ok(readings) => report_readings(&config, "Redis Set", "redis_set", &readings),
+            Err(e) => error!("Redis Set parse error: {}", e),
         },
         Err(e) => error!("Redis SMEMBERS error: {}", e),
     }
-    if let Err(e) = redis_data::delete_key(set_key).await {
+    if let Err(e) = redis_data::delete_key(&config.redis, set_key).await {
         error!("Redis DEL error: {}", e);
     } else {
         info!("Redis set key deleted successfully.");
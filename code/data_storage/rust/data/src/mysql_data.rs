@@ -4,16 +4,15 @@
 use sqlx::mysql::MySqlPoolOptions;
 use sqlx::{MySqlPool, Row};
 use log::info;
-use crate::sensor_data_json_helper::validate_sensor_json;
+use crate::config::MySqlConfig;
+use crate::sensor_reading::SensorReading;
 
-const DATABASE_URL: &str = "mysql://root:@localhost:3306/sensor_data_db";
-
-async fn get_pool() -> Result<MySqlPool, sqlx::Error> {
-    MySqlPoolOptions::new().max_connections(5).connect(DATABASE_URL).await
+async fn get_pool(config: &MySqlConfig) -> Result<MySqlPool, sqlx::Error> {
+    MySqlPoolOptions::new().max_connections(config.max_connections).connect(&config.url).await
 }
 
-pub async fn setup_database() -> Result<(), String> {
-    let pool = get_pool().await.map_err(|e| format!("Pool error: {}", e))?;
+pub async fn setup_database(config: &MySqlConfig) -> Result<(), String> {
+    let pool = get_pool(config).await.map_err(|e| format!("Pool error: {}", e))?;
     sqlx::query("CREATE DATABASE IF NOT EXISTS sensor_data_db").execute(&pool).await.map_err(|e| format!("Create DB error: {}", e))?;
     sqlx::query(r#"CREATE TABLE IF NOT EXISTS sensor_data (
         id BIGINT AUTO_INCREMENT PRIMARY KEY,
@@ -28,42 +27,39 @@ pub async fn setup_database() -> Result<(), String> {
     Ok(())
 }
 
-pub async fn insert_json_into_table(json_str: &str) -> Result<(), String> {
-    let parsed = validate_sensor_json(json_str)?;
-    let pool = get_pool().await.map_err(|e| format!("Pool error: {}", e))?;
+pub async fn insert_json_into_table(config: &MySqlConfig, json_str: &str) -> Result<(), String> {
+    let reading = SensorReading::from_json(json_str).map_err(|e| e.to_string())?;
+    let pool = get_pool(config).await.map_err(|e| format!("Pool error: {}", e))?;
     let query = "INSERT INTO sensor_data (recorded, location, sensor, measurement, units, value) VALUES (?, ?, ?, ?, ?, ?)";
     sqlx::query(query)
-        .bind(parsed["recorded"].as_i64().unwrap())
-        .bind(parsed["location"].as_str().unwrap())
-        .bind(parsed["sensor"].as_str().unwrap())
-        .bind(parsed["measurement"].as_str().unwrap())
-        .bind(parsed["units"].as_str().unwrap())
-        .bind(parsed["value"].as_f64().unwrap())
+        .bind(reading.recorded)
+        .bind(&reading.location)
+        .bind(&reading.sensor)
+        .bind(&reading.measurement)
+        .bind(&reading.units)
+        .bind(reading.value)
         .execute(&pool).await.map_err(|e| format!("Insert error: {}", e))?;
     info!("Data inserted successfully.");
     Ok(())
 }
 
-pub async fn fetch_data_as_csv() -> Result<String, String> {
-    let pool = get_pool().await.map_err(|e| format!("Pool error: {}", e))?;
+pub async fn fetch_readings(config: &MySqlConfig) -> Result<Vec<SensorReading>, String> {
+    let pool = get_pool(config).await.map_err(|e| format!("Pool error: {}", e))?;
     let rows = sqlx::query("SELECT recorded, location, sensor, measurement, units, CAST(value AS DOUBLE) as value FROM sensor_data")
         .fetch_all(&pool).await.map_err(|e| format!("Fetch error: {}", e))?;
-    let mut csv_data = String::from("recorded,location,sensor,measurement,units,value\n");
-    for row in rows {
-        csv_data.push_str(&format!("{},{},{},{},{},{}\n",
-            row.get::<i64, _>("recorded"),
-            row.get::<String, _>("location"),
-            row.get::<String, _>("sensor"),
-            row.get::<String, _>("measurement"),
-            row.get::<String, _>("units"),
-            row.get::<f64, _>("value"),
-        ));
-    }
-    Ok(csv_data)
+    let readings = rows.iter().map(|row| SensorReading {
+        recorded: row.get("recorded"),
+        location: row.get("location"),
+        sensor: row.get("sensor"),
+        measurement: row.get("measurement"),
+        units: row.get("units"),
+        value: row.get("value"),
+    }).collect();
+    Ok(readings)
 }
 
-pub async fn delete_row(recorded: i64, location: &str, sensor: &str) -> Result<(), String> {
-    let pool = get_pool().await.map_err(|e| format!("Pool error: {}", e))?;
+pub async fn delete_row(config: &MySqlConfig, recorded: i64, location: &str, sensor: &str) -> Result<(), String> {
+    let pool = get_pool(config).await.map_err(|e| format!("Pool error: {}", e))?;
     let query = "DELETE FROM sensor_data WHERE recorded = ? AND location = ? AND sensor = ?";
     sqlx::query(query)
         .bind(recorded)
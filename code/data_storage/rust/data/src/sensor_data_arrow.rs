@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_data_csv::CSV_HEADER;
+use crate::sensor_reading::SensorReading;
+use arrow::array::{ArrayRef, Float64Array, Int64Array, StringDictionaryBuilder};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, SchemaRef};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A columnar export format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Arrow,
+}
+
+impl ExportFormat {
+    /// Every format, in the order files are written.
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Parquet, ExportFormat::Arrow];
+
+    /// File extension of an exported file.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Arrow => "arrow",
+        }
+    }
+}
+
+/// Columns in `CSV_HEADER` order: i64 `recorded`, dictionary-encoded strings, f64 `value`.
+pub fn sensor_schema() -> SchemaRef {
+    let dictionary = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+    Arc::new(Schema::new(vec![
+        Field::new(CSV_HEADER[0], DataType::Int64, false),
+        Field::new(CSV_HEADER[1], dictionary.clone(), false),
+        Field::new(CSV_HEADER[2], dictionary.clone(), false),
+        Field::new(CSV_HEADER[3], dictionary.clone(), false),
+        Field::new(CSV_HEADER[4], dictionary, false),
+        Field::new(CSV_HEADER[5], DataType::Float64, false),
+    ]))
+}
+
+fn dictionary_column<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+    let mut builder = StringDictionaryBuilder::<Int32Type>::new();
+    for value in values {
+        builder.append_value(value);
+    }
+    Arc::new(builder.finish())
+}
+
+/// One record batch holding every reading.
+pub fn readings_to_record_batch(readings: &[SensorReading]) -> Result<RecordBatch, String> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from_iter_values(readings.iter().map(|r| r.recorded))),
+        dictionary_column(readings.iter().map(|r| r.location.as_str())),
+        dictionary_column(readings.iter().map(|r| r.sensor.as_str())),
+        dictionary_column(readings.iter().map(|r| r.measurement.as_str())),
+        dictionary_column(readings.iter().map(|r| r.units.as_str())),
+        Arc::new(Float64Array::from_iter_values(readings.iter().map(|r| r.value))),
+    ];
+    RecordBatch::try_new(sensor_schema(), columns).map_err(|e| format!("Record batch error: {}", e))
+}
+
+/// A complete Parquet or Arrow IPC file for the readings.
+pub fn export_readings(format: ExportFormat, readings: &[SensorReading]) -> Result<Vec<u8>, String> {
+    let batch = readings_to_record_batch(readings)?;
+    match format {
+        ExportFormat::Parquet => {
+            let mut writer = ArrowWriter::try_new(Vec::new(), batch.schema(), None)
+                .map_err(|e| format!("Parquet writer error: {}", e))?;
+            writer.write(&batch).map_err(|e| format!("Parquet write error: {}", e))?;
+            writer.into_inner().map_err(|e| format!("Parquet write error: {}", e))
+        }
+        ExportFormat::Arrow => {
+            let mut writer = FileWriter::try_new(Vec::new(), &batch.schema())
+                .map_err(|e| format!("Arrow writer error: {}", e))?;
+            writer.write(&batch).map_err(|e| format!("Arrow write error: {}", e))?;
+            writer.finish().map_err(|e| format!("Arrow write error: {}", e))?;
+            writer.into_inner().map_err(|e| format!("Arrow write error: {}", e))
+        }
+    }
+}
+
+/// Write the readings to `<dir>/<name>.<extension>`, returning the file's path.
+pub fn write_export(dir: &Path, name: &str, format: ExportFormat, readings: &[SensorReading]) -> Result<PathBuf, String> {
+    let path = dir.join(format!("{}.{}", name, format.extension()));
+    let bytes = export_readings(format, readings)?;
+    fs::write(&path, bytes).map_err(|e| format!("Write error for {}: {}", path.display(), e))?;
+    Ok(path)
+}
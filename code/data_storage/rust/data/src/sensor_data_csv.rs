@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_reading::SensorReading;
+use sensor_csv::{csv_record, csv_rows};
+
+/// Column order of every CSV report; matches the field order of `SensorReading`.
+pub const CSV_HEADER: [&str; 6] = ["recorded", "location", "sensor", "measurement", "units", "value"];
+
+/// A complete CSV document: the header, then every reading.
+pub fn readings_to_csv(readings: &[SensorReading]) -> String {
+    csv_record(CSV_HEADER) + &csv_rows(readings)
+}
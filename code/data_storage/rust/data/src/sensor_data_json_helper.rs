@@ -1,8 +1,10 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
+use serde::Serialize;
 use serde_json::{Value, json};
 use log::{error};
+use std::fmt;
 
 pub fn create_sample_json() -> String {
     json!({
@@ -15,15 +17,97 @@ pub fn create_sample_json() -> String {
     }).to_string()
 }
 
-pub fn validate_sensor_json(json_str: &str) -> Result<Value, String> {
+/// Earliest accepted `recorded` timestamp (2000-01-01T00:00:00Z).
+pub const MIN_RECORDED: i64 = 946_684_800;
+/// Latest accepted `recorded` timestamp (2100-01-01T00:00:00Z).
+pub const MAX_RECORDED: i64 = 4_102_444_800;
+
+/// A single problem found while validating a sensor reading.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        FieldError { field: field.to_string(), message: message.into() }
+    }
+}
+
+/// Every problem found in a sensor reading, in field order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.0.iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Parse a sensor JSON string and check every field's type and range.
+///
+/// All violations are collected so callers can report them at once.
+pub fn validate_sensor_json(json_str: &str) -> Result<Value, ValidationErrors> {
     let parsed: Value = serde_json::from_str(json_str)
-        .map_err(|e| format!("JSON parse error: {}", e))?;
-    let required = ["recorded", "location", "sensor", "measurement", "units", "value"];
-    for &field in &required {
-        if !parsed.get(field).is_some() {
-            error!("Missing field: {}", field);
-            return Err(format!("Missing field: {}", field));
+        .map_err(|e| ValidationErrors(vec![FieldError::new("body", format!("JSON parse error: {}", e))]))?;
+    if !parsed.is_object() {
+        return Err(ValidationErrors(vec![FieldError::new("body", "must be a JSON object")]));
+    }
+
+    let mut errors = Vec::new();
+    match parsed.get("recorded") {
+        None => errors.push(FieldError::new("recorded", "is required")),
+        Some(v) => match as_integer(v) {
+            None => errors.push(FieldError::new("recorded", "must be an integer epoch timestamp")),
+            Some(recorded) if !(MIN_RECORDED..=MAX_RECORDED).contains(&recorded) => errors.push(FieldError::new(
+                "recorded",
+                format!("must be between {} and {}", MIN_RECORDED, MAX_RECORDED),
+            )),
+            Some(_) => {}
+        },
+    }
+    for (field, max_len) in [("location", 255), ("sensor", 255), ("measurement", 255), ("units", 50)] {
+        match parsed.get(field) {
+            None => errors.push(FieldError::new(field, "is required")),
+            Some(Value::String(s)) if s.trim().is_empty() => errors.push(FieldError::new(field, "must not be empty")),
+            Some(Value::String(s)) if s.chars().count() > max_len => errors.push(FieldError::new(
+                field,
+                format!("must be at most {} characters", max_len),
+            )),
+            Some(Value::String(_)) => {}
+            Some(_) => errors.push(FieldError::new(field, "must be a string")),
         }
     }
-    Ok(parsed)
+    match parsed.get("value") {
+        None => errors.push(FieldError::new("value", "is required")),
+        Some(v) => match as_number(v) {
+            None => errors.push(FieldError::new("value", "must be a number")),
+            Some(value) if !value.is_finite() => errors.push(FieldError::new("value", "must be finite")),
+            Some(_) => {}
+        },
+    }
+
+    if errors.is_empty() {
+        Ok(parsed)
+    } else {
+        error!("Invalid sensor data: {}", ValidationErrors(errors.clone()));
+        Err(ValidationErrors(errors))
+    }
+}
+
+/// Read an integer, accepting integer strings for older devices.
+pub fn as_integer(v: &Value) -> Option<i64> {
+    v.as_i64().or_else(|| v.as_str().and_then(|s| s.trim().parse::<i64>().ok()))
+}
+
+/// Read a number, accepting numeric strings for older devices.
+pub fn as_number(v: &Value) -> Option<f64> {
+    v.as_f64().or_else(|| v.as_str().and_then(|s| s.trim().parse::<f64>().ok()))
 }
@@ -1,31 +1,66 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
-mod data;
+mod config;
+mod problem;
+#[cfg(test)]
+mod tests;
 
 use actix_web::{get, App, HttpServer, Responder};
 use actix_web::{post, web, HttpResponse};
-use actix_web::{route, HttpRequest};
-use std::env;
-use data::sensor_data_json_helper;
-use data::redis_data_access::RedisDataAccess;
-use data::sensor_data_access_trait::SensorDataAccess;
-use data::mongo_data_access::MongoDataAccess;
-use data::cassandra_data_access::CassandraDataAccess;
-use data::mysql_data_access::MySQLDataAccess;
-use data::postgres_data_access::PostgresDataAccess;
-
-fn get_data_access() -> Box<dyn SensorDataAccess + Send> {
-    let data_access_type = env::var("DATA_ACCESS").unwrap_or_else(|_| "redis".to_string());
-
-    match data_access_type.as_str() {
-        "redis" => Box::new(RedisDataAccess::new()),
-        "mongo" => Box::new(MongoDataAccess::new()),
-        "cassandra" => Box::new(CassandraDataAccess::new()),
-        "mysql" => Box::new(MySQLDataAccess::new()),
-        "postgres" => Box::new(PostgresDataAccess::new()),
-        _ => panic!("Unsupported DATA_ACCESS type: {}", data_access_type),
+use actix_web::{delete, route, HttpRequest};
+use actix_web::http::header;
+use clap::Parser;
+use config::{Args, Command, Config};
+use futures::stream::StreamExt;
+use std::sync::Arc;
+use sensor_store::fanout_data_access::{FanoutDataAccess, NamedStore};
+use sensor_store::health::HealthCheck;
+use sensor_store::jsonl_data_access::JsonlDataAccess;
+use sensor_store::memory_data_access::MemoryDataAccess;
+use sensor_store::migrations::{migrate, run_migrate};
+use sensor_store::redis_data_access::RedisDataAccess;
+use sensor_store::retention::{run_sweeper, RetentionPolicy};
+use sensor_store::report_writer::{first_reading, negotiate, render_report, report_stream, ReportFormat, ReportWriter};
+use sensor_store::sensor_data_access_trait::{DynSensorDataAccess, LogOutcome};
+use sensor_store::sensor_data_arrow::{collect_readings, export_readings, ExportFormat, MAX_EXPORT_READINGS};
+use sensor_store::sensor_data_batch::{log_batch, parse_batch};
+use sensor_store::sensor_data_verify::run_verify;
+use sensor_store::mongo_data_access::MongoDataAccess;
+use sensor_store::cassandra_data_access::CassandraDataAccess;
+use sensor_store::mysql_data_access::MySQLDataAccess;
+use sensor_store::postgres_data_access::PostgresDataAccess;
+use sensor_store::sensor_query::SensorQuery;
+use sensor_store::sensor_reading::{ReadingKey, SensorReading};
+use sensor_store::sensor_stats::{Bucket, StatsParams};
+use sensor_store::sqlite_data_access::SqliteDataAccess;
+use problem::{json_config, path_config, payload_config, query_config, Problem};
+
+// The storage backend every handler shares, connected once at startup.
+type DataAccess = web::Data<dyn DynSensorDataAccess>;
+
+async fn connect_data_access(name: &str, config: &Config, retention: &RetentionPolicy) -> Arc<dyn DynSensorDataAccess> {
+    let data_access: Result<Arc<dyn DynSensorDataAccess>, _> = match name {
+        "redis" => RedisDataAccess::connect(&config.redis).await.map(|d| Arc::new(d) as Arc<dyn DynSensorDataAccess>),
+        "mongo" => MongoDataAccess::connect(&config.mongo, retention).await.map(|d| Arc::new(d) as Arc<dyn DynSensorDataAccess>),
+        "cassandra" => CassandraDataAccess::connect(&config.cassandra, retention).await.map(|d| Arc::new(d) as Arc<dyn DynSensorDataAccess>),
+        "mysql" => MySQLDataAccess::connect(&config.mysql).await.map(|d| Arc::new(d) as Arc<dyn DynSensorDataAccess>),
+        "postgres" => PostgresDataAccess::connect(&config.postgres).await.map(|d| Arc::new(d) as Arc<dyn DynSensorDataAccess>),
+        "sqlite" => SqliteDataAccess::connect(&config.sqlite).await.map(|d| Arc::new(d) as Arc<dyn DynSensorDataAccess>),
+        "jsonl" => JsonlDataAccess::connect(&config.jsonl).await.map(|d| Arc::new(d) as Arc<dyn DynSensorDataAccess>),
+        "memory" => Ok(Arc::new(MemoryDataAccess::new()) as Arc<dyn DynSensorDataAccess>),
+        _ => panic!("Unsupported DATA_ACCESS type: {}", name),
+    };
+    data_access.unwrap_or_else(|e| panic!("Failed to connect to {}: {}", name, e))
+}
+
+// Every backend listed in DATA_ACCESS, connected in order with the primary first.
+async fn connect_stores(config: &Config, retention: &RetentionPolicy) -> Vec<NamedStore> {
+    let mut stores = Vec::new();
+    for name in config.stores() {
+        stores.push((name.to_string(), connect_data_access(name, config, retention).await));
     }
+    stores
 }
 
 // This function is the handler for GET requests on the root path "/".
@@ -36,6 +71,25 @@ async fn hello() -> impl Responder {
     }))
 }
 
+// This function is the liveness check: the process is up and serving, whatever the state of its backends.
+#[get("/healthz")]
+async fn healthz() -> impl Responder {
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": "ok"
+    }))
+}
+
+// This function is the readiness check: the backends answer and their migrations are applied, with each one's latency.
+#[get("/readyz")]
+async fn readyz(health: web::Data<HealthCheck>) -> HttpResponse {
+    let readiness = health.readiness().await;
+    if readiness.ready {
+        HttpResponse::Ok().json(readiness)
+    } else {
+        HttpResponse::ServiceUnavailable().json(readiness)
+    }
+}
+
 // This function is the handler for POST requests on the "/echo" path.
 #[post("/echo")]
 async fn echo(req_body: web::Json<serde_json::Value>) -> HttpResponse {
@@ -44,98 +98,261 @@ async fn echo(req_body: web::Json<serde_json::Value>) -> HttpResponse {
 
 // This function is the handler for POST requests on the "/log" path.
 #[post("/log")]
-async fn log(req_body: web::Json<serde_json::Value>) -> HttpResponse {
+async fn log(sensor_data_access: DataAccess, req_body: web::Json<serde_json::Value>) -> Result<HttpResponse, Problem> {
     let json_data = req_body.into_inner().to_string();
-    let sensor_data_access = get_data_access();
-
-    match sensor_data_access.log_sensor_data(&json_data).await {
-        Ok(result) => {
-            match result {
-                Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-                    "message": "Data logged successfully"
-                })),
-                Err(e) => HttpResponse::InternalServerError()
-                    .body(format!("Failed to log sensor data: {}", e)),
-            }
-        }
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Task join error: {}", e)),
+    let reading = SensorReading::from_json(&json_data).map_err(Problem::invalid_reading)?;
+
+    let outcome = sensor_data_access.log_sensor_data(&reading).await
+        .map_err(|e| Problem::storage("Failed to log sensor data", e))?;
+    Ok(HttpResponse::Ok().json(log_response(outcome)))
+}
+
+// A retried post still succeeds, so devices can tell a duplicate apart without treating it as an error.
+fn log_response(outcome: LogOutcome) -> serde_json::Value {
+    let message = match outcome {
+        LogOutcome::Created => "Data logged successfully",
+        LogOutcome::Duplicate => "Data already logged",
+    };
+    serde_json::json!({
+        "message": message,
+        "outcome": outcome
+    })
+}
+
+// This function is the handler for POST requests on the "/log/batch" path, taking a JSON array or NDJSON.
+#[post("/log/batch")]
+async fn log_batch_readings(sensor_data_access: DataAccess, body: Result<web::Bytes, actix_web::Error>) -> Result<HttpResponse, Problem> {
+    let items = parse_batch(&body.map_err(Problem::from_payload)?).map_err(Problem::bad_request)?;
+    let batch_report = log_batch(sensor_data_access.get_ref(), items).await
+        .map_err(|e| Problem::storage("Failed to log sensor data", e))?;
+    Ok(HttpResponse::Ok().json(batch_report))
+}
+
+// `Link` header pointing at the page after this one, in the same format.
+fn next_link(query: &SensorQuery, format: &ReportFormat, next: &str) -> String {
+    let mut params = serde_urlencoded::to_string(query.next_page(next)).unwrap_or_default();
+    if let Some(format) = &format.format {
+        params.push('&');
+        params.push_str(&serde_urlencoded::to_string([("format", format)]).unwrap_or_default());
+    }
+    format!("</report?{}>; rel=\"next\"", params)
+}
+
+// Headers shared by streamed and paged reports.
+fn report_response(writer: &dyn ReportWriter) -> actix_web::HttpResponseBuilder {
+    let mut response = HttpResponse::Ok();
+    response.content_type(writer.content_type());
+    response.insert_header((header::VARY, "Accept"));
+    if writer.attachment() {
+        response.insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"report.{}\"", writer.name()),
+        ));
     }
+    response
 }
 
 // This function is the handler for GET requests on the root path "/report".
+// Optional query parameters: from, to, location, sensor, measurement, limit, cursor, format.
+// Without `format`, the report format is negotiated from the `Accept` header.
 #[get("/report")]
-async fn report() -> impl Responder {
-    let sensor_data_access = get_data_access();
-
-    match sensor_data_access.fetch_sensor_data().await {
-        Ok(result) => {
-            match result {
-                Ok(json_strings) => {
-                    // Combine all JSON strings and convert to CSV
-                    if json_strings.is_empty() {
-                        return HttpResponse::Ok()
-                            .content_type("text/csv")
-                            .body("No data available");
-                    }
-                    // Convert first JSON string to CSV (or combine multiple)
-                    let json_str = &json_strings[0];
-                    match sensor_data_json_helper::json_to_csv(json_str) {
-                        Ok(csv) => HttpResponse::Ok()
-                            .content_type("text/csv")
-                            .body(csv),
-                        Err(e) => HttpResponse::InternalServerError()
-                            .body(format!("Failed to convert to CSV: {}", e)),
-                    }
-                }
-                Err(e) => HttpResponse::InternalServerError()
-                    .body(format!("Failed to fetch sensor data: {}", e)),
-            }
-        }
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Task join error: {}", e)),
+async fn report(
+    sensor_data_access: DataAccess,
+    req: HttpRequest,
+    query: web::Query<SensorQuery>,
+    format: web::Query<ReportFormat>,
+) -> Result<HttpResponse, Problem> {
+    query.validate().map_err(Problem::bad_request)?;
+    let accept = req.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let writer = negotiate(format.format.as_deref(), accept)?;
+
+    // Without a page limit, stream the whole report with chunked transfer encoding
+    if query.limit.is_none() {
+        let mut rows = sensor_data_access.stream_sensor_data(&query);
+        let first = first_reading(&mut rows).await
+            .map_err(|e| Problem::storage("Failed to fetch sensor data", e))?;
+        return Ok(report_response(writer).streaming(report_stream(writer, first, rows).map(|chunk| {
+            chunk.map(web::Bytes::from).map_err(|e| -> Box<dyn std::error::Error> { e })
+        })));
+    }
+
+    let page = sensor_data_access.fetch_sensor_data(&query).await
+        .map_err(|e| Problem::storage("Failed to fetch sensor data", e))?;
+    let mut response = report_response(writer);
+    if let Some(next) = &page.next {
+        response.insert_header((header::LINK, next_link(&query, &format, next)));
     }
+    Ok(response.body(render_report(writer, &page.readings)))
+}
+
+// This function is the handler for GET requests on the "/export" path.
+// Returns every reading matching the report filters as a Parquet (default) or
+// Arrow IPC file, chosen by the `format` query parameter. Exports are built in memory,
+// so queries matching more than MAX_EXPORT_READINGS readings are rejected.
+#[get("/export")]
+async fn export(
+    sensor_data_access: DataAccess,
+    query: web::Query<SensorQuery>,
+    format: web::Query<ReportFormat>,
+) -> Result<HttpResponse, Problem> {
+    query.validate().map_err(Problem::bad_request)?;
+    let format = ExportFormat::parse(format.format.as_deref()).map_err(Problem::bad_request)?;
+
+    let readings = collect_readings(sensor_data_access.stream_sensor_data(&query), MAX_EXPORT_READINGS).await
+        .map_err(|e| Problem::storage("Failed to fetch sensor data", e))?;
+    let file = export_readings(format, &readings)
+        .map_err(|e| Problem::internal(format!("Failed to export sensor data: {}", e)))?;
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"sensor_data.{}\"", format.extension()),
+        ))
+        .body(file))
+}
+
+// This function is the handler for GET requests on the "/stats" path, aggregating per time bucket.
+#[get("/stats")]
+async fn stats(
+    sensor_data_access: DataAccess,
+    query: web::Query<SensorQuery>,
+    params: web::Query<StatsParams>,
+) -> Result<HttpResponse, Problem> {
+    let bucket = Bucket::parse(params.bucket.as_deref()).map_err(Problem::bad_request)?;
+
+    let results = sensor_data_access.aggregate_sensor_data(&query, bucket).await
+        .map_err(|e| Problem::storage("Failed to aggregate sensor data", e))?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "bucket": bucket.seconds(),
+        "stats": results
+    })))
+}
+
+// This function is the handler for GET requests on a single reading's path.
+#[get("/readings/{location}/{sensor}/{recorded}")]
+async fn get_reading(sensor_data_access: DataAccess, key: web::Path<ReadingKey>) -> Result<HttpResponse, Problem> {
+    let reading = sensor_data_access.get_sensor_reading(&key).await
+        .map_err(|e| Problem::storage("Failed to fetch sensor reading", e))?;
+    Ok(HttpResponse::Ok().json(reading))
+}
+
+// This function is the handler for DELETE requests on a single reading's path.
+#[delete("/readings/{location}/{sensor}/{recorded}")]
+async fn delete_reading(sensor_data_access: DataAccess, key: web::Path<ReadingKey>) -> Result<HttpResponse, Problem> {
+    sensor_data_access.delete_sensor_reading(&key).await
+        .map_err(|e| Problem::storage("Failed to delete sensor reading", e))?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "deleted"
+    })))
 }
 
 // This function handles purge GET and POST requests on the "/purge" path.
 #[route("/purge", method = "GET", method = "POST")]
-async fn purge(req: HttpRequest, _body: web::Bytes) -> impl Responder {
-    let sensor_data_access = get_data_access();
-
-    match req.method() {
-        &actix_web::http::Method::GET | &actix_web::http::Method::POST => {
-            match sensor_data_access.purge_sensor_data().await {
-                Ok(result) => {
-                    match result {
-                        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-                            "message": "purged"
-                        })),
-                        Err(e) => HttpResponse::InternalServerError()
-                            .body(format!("Failed to purge sensor data: {}", e)),
-                    }
-                }
-                Err(e) => HttpResponse::InternalServerError()
-                    .body(format!("Task join error: {}", e)),
-            }
-        }
-        _ => HttpResponse::MethodNotAllowed().finish(),
-    }
+async fn purge(sensor_data_access: DataAccess) -> Result<HttpResponse, Problem> {
+    sensor_data_access.purge_sensor_data().await
+        .map_err(|e| Problem::storage("Failed to purge sensor data", e))?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "purged"
+    })))
+}
+
+// This function reports the retention windows in seconds and how the backend enforces them.
+#[get("/admin/retention")]
+async fn admin_retention(sensor_data_access: DataAccess, retention: web::Data<RetentionPolicy>) -> HttpResponse {
+    HttpResponse::Ok().json(retention.report(sensor_data_access.expires_natively()))
+}
+
+// Every route of the service and its extractor settings; the app supplies the shared state.
+fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(json_config())
+        .app_data(query_config())
+        .app_data(path_config())
+        .app_data(payload_config())
+        .service(hello)
+        .service(healthz)
+        .service(readyz)
+        .service(echo)
+        .service(log)
+        .service(log_batch_readings)
+        .service(report)
+        .service(export)
+        .service(stats)
+        .service(get_reading)
+        .service(delete_reading)
+        .service(purge)
+        .service(admin_retention);
 }
 
 // The #[actix_web::main] macro sets up an async runtime for your main function.
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let config = Config::load(&args).unwrap_or_else(|e| {
+        eprintln!("Invalid configuration: {}", e);
+        std::process::exit(2);
+    });
+    if args.print_config {
+        print!("{}", config.to_redacted_toml());
+        return Ok(());
+    }
+
+    let retention = RetentionPolicy::from_config(&config.retention);
+    let stores = connect_stores(&config, &retention).await;
+
+    // Each backend keeps its own schema, so migrations run store by store.
+    if let Some(Command::Migrate { status, dry_run }) = args.command {
+        for (name, store) in &stores {
+            if stores.len() > 1 {
+                println!("== {}", name);
+            }
+            if let Err(e) = run_migrate(&**store, status, dry_run).await {
+                eprintln!("Migration failed for {}: {}", name, e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Command::Verify { diff }) = &args.command {
+        if stores.len() < 2 {
+            eprintln!("Verify needs two or more backends listed in DATA_ACCESS");
+            std::process::exit(2);
+        }
+        match run_verify(&stores, diff).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Verify failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if config.migrate_on_startup {
+        for (name, store) in &stores {
+            if let Err(e) = migrate(&**store).await {
+                eprintln!("Migration failed for {}: {}", name, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    // Readiness reports each store, so it keeps them apart from the fan-out.
+    let health = web::Data::new(HealthCheck::new(stores.clone(), config.fanout.policy));
+    let data_access: DataAccess = web::Data::from(FanoutDataAccess::combine(stores, config.fanout.policy));
+
+    if retention.enforcement(data_access.expires_natively()).sweeps() {
+        actix_web::rt::spawn(run_sweeper(data_access.clone().into_inner(), retention.clone()));
+    }
+    let retention = web::Data::new(retention);
+
     // Create a new HttpServer.
-    HttpServer::new(|| {
-        // Create a new App instance and register the `hello` service.
-        App::new().service(hello)
-            .service(echo)
-            .service(log)
-            .service(report)
-            .service(purge)
+    HttpServer::new(move || {
+        // Create a new App instance with the shared state and every route.
+        App::new().app_data(data_access.clone())
+            .app_data(retention.clone())
+            .app_data(health.clone())
+            .configure(routes)
     })
-    // Bind the server to the local address "127.0.0.1" and port 8080.
-    .bind(("0.0.0.0", 8080))?
+    // Bind the server to the configured address.
+    .bind(config.bind.as_str())?
     // Start the server and wait for it to complete.
     .run()
     .await
@@ -1,10 +0,0 @@
-// SPDX-License-Identifier: GPL-3.0-or-later
-// Copyright (C) 2025-2026 ggeoffre, LLC
-
-pub mod cassandra_data_access;
-pub mod mongo_data_access;
-pub mod mysql_data_access;
-pub mod postgres_data_access;
-pub mod redis_data_access;
-pub mod sensor_data_access_trait;
-pub mod sensor_data_json_helper;
@@ -1,159 +1,369 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // Copyright (C) 2025-2026 ggeoffre, LLC
 
-mod data;
+mod config;
+mod problem;
+#[cfg(test)]
+mod tests;
 
-use axum::{Router, body::Bytes, response::IntoResponse};
+use axum::{Router, body::Bytes, extract::{DefaultBodyLimit, FromRef, Path, Query, State}, response::IntoResponse};
+use axum::extract::rejection::{BytesRejection, PathRejection, QueryRejection};
 use axum::response::Response;
-use axum::http::{header, HeaderValue, StatusCode};
-use std::net::SocketAddr;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use clap::Parser;
+use config::{Args, Command, Config};
 use tokio::net::TcpListener;
-use std::env;
-use data::sensor_data_json_helper::{json_to_csv};
-use data::redis_data_access::RedisDataAccess;
-use data::sensor_data_access_trait::SensorDataAccess;
-use data::mongo_data_access::MongoDataAccess;
-use data::cassandra_data_access::CassandraDataAccess;
-use data::mysql_data_access::MySQLDataAccess;
-use data::postgres_data_access::PostgresDataAccess;
-
-fn get_data_access() -> Box<dyn SensorDataAccess + Send> {
-    let data_access_type = env::var("DATA_ACCESS").unwrap_or_else(|_| "redis".to_string());
-
-    match data_access_type.as_str() {
-        "redis" => Box::new(RedisDataAccess::new()),
-        "mongo" => Box::new(MongoDataAccess::new()),
-        "cassandra" => Box::new(CassandraDataAccess::new()),
-        "mysql" => Box::new(MySQLDataAccess::new()),
-        "postgres" => Box::new(PostgresDataAccess::new()),
-        _ => panic!("Unsupported DATA_ACCESS type: {}", data_access_type),
+use std::sync::Arc;
+use sensor_store::fanout_data_access::{FanoutDataAccess, NamedStore};
+use sensor_store::health::HealthCheck;
+use sensor_store::jsonl_data_access::JsonlDataAccess;
+use sensor_store::memory_data_access::MemoryDataAccess;
+use sensor_store::migrations::{migrate, run_migrate};
+use sensor_store::redis_data_access::RedisDataAccess;
+use sensor_store::retention::{run_sweeper, RetentionPolicy};
+use sensor_store::report_writer::{first_reading, negotiate, render_report, report_stream, ReportFormat, ReportWriter};
+use sensor_store::sensor_data_access_trait::{DynSensorDataAccess, LogOutcome};
+use sensor_store::sensor_data_arrow::{collect_readings, export_readings, ExportFormat, MAX_EXPORT_READINGS};
+use sensor_store::sensor_data_batch::{log_batch, parse_batch};
+use sensor_store::sensor_data_verify::run_verify;
+use sensor_store::mongo_data_access::MongoDataAccess;
+use sensor_store::cassandra_data_access::CassandraDataAccess;
+use sensor_store::mysql_data_access::MySQLDataAccess;
+use sensor_store::postgres_data_access::PostgresDataAccess;
+use sensor_store::sensor_query::SensorQuery;
+use sensor_store::sensor_reading::{ReadingKey, SensorReading};
+use sensor_store::sensor_stats::{Bucket, StatsParams};
+use sensor_store::sqlite_data_access::SqliteDataAccess;
+use problem::{Problem, BODY_LIMIT};
+
+// The storage backend every handler shares, connected once at startup
+type DataAccess = Arc<dyn DynSensorDataAccess>;
+
+// Everything handlers share; each extracts the parts it needs with `State`
+#[derive(Clone)]
+struct AppState {
+    data_access: DataAccess,
+    retention: Arc<RetentionPolicy>,
+    health: Arc<HealthCheck>,
+}
+
+impl FromRef<AppState> for DataAccess {
+    fn from_ref(state: &AppState) -> Self {
+        state.data_access.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<RetentionPolicy> {
+    fn from_ref(state: &AppState) -> Self {
+        state.retention.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<HealthCheck> {
+    fn from_ref(state: &AppState) -> Self {
+        state.health.clone()
     }
 }
 
+async fn connect_data_access(name: &str, config: &Config, retention: &RetentionPolicy) -> DataAccess {
+    let data_access: Result<DataAccess, _> = match name {
+        "redis" => RedisDataAccess::connect(&config.redis).await.map(|d| Arc::new(d) as DataAccess),
+        "mongo" => MongoDataAccess::connect(&config.mongo, retention).await.map(|d| Arc::new(d) as DataAccess),
+        "cassandra" => CassandraDataAccess::connect(&config.cassandra, retention).await.map(|d| Arc::new(d) as DataAccess),
+        "mysql" => MySQLDataAccess::connect(&config.mysql).await.map(|d| Arc::new(d) as DataAccess),
+        "postgres" => PostgresDataAccess::connect(&config.postgres).await.map(|d| Arc::new(d) as DataAccess),
+        "sqlite" => SqliteDataAccess::connect(&config.sqlite).await.map(|d| Arc::new(d) as DataAccess),
+        "jsonl" => JsonlDataAccess::connect(&config.jsonl).await.map(|d| Arc::new(d) as DataAccess),
+        "memory" => Ok(Arc::new(MemoryDataAccess::new()) as DataAccess),
+        _ => panic!("Unsupported DATA_ACCESS type: {}", name),
+    };
+    data_access.unwrap_or_else(|e| panic!("Failed to connect to {}: {}", name, e))
+}
+
+// Every backend listed in DATA_ACCESS, connected in order with the primary first
+async fn connect_stores(config: &Config, retention: &RetentionPolicy) -> Vec<NamedStore> {
+    let mut stores = Vec::new();
+    for name in config.stores() {
+        stores.push((name.to_string(), connect_data_access(name, config, retention).await));
+    }
+    stores
+}
+
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+    let config = Config::load(&args).unwrap_or_else(|e| {
+        eprintln!("Invalid configuration: {}", e);
+        std::process::exit(2);
+    });
+    if args.print_config {
+        print!("{}", config.to_redacted_toml());
+        return;
+    }
+
+    let retention = RetentionPolicy::from_config(&config.retention);
+    let stores = connect_stores(&config, &retention).await;
+
+    // Each backend keeps its own schema, so migrations run store by store
+    if let Some(Command::Migrate { status, dry_run }) = args.command {
+        for (name, store) in &stores {
+            if stores.len() > 1 {
+                println!("== {}", name);
+            }
+            if let Err(e) = run_migrate(&**store, status, dry_run).await {
+                eprintln!("Migration failed for {}: {}", name, e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(Command::Verify { diff }) = &args.command {
+        if stores.len() < 2 {
+            eprintln!("Verify needs two or more backends listed in DATA_ACCESS");
+            std::process::exit(2);
+        }
+        match run_verify(&stores, diff).await {
+            Ok(true) => return,
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Verify failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if config.migrate_on_startup {
+        for (name, store) in &stores {
+            if let Err(e) = migrate(&**store).await {
+                eprintln!("Migration failed for {}: {}", name, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    // Readiness reports each store, so it keeps them apart from the fan-out
+    let health = Arc::new(HealthCheck::new(stores.clone(), config.fanout.policy));
+    let data_access = FanoutDataAccess::combine(stores, config.fanout.policy);
+
+    if retention.enforcement(data_access.expires_natively()).sweeps() {
+        tokio::spawn(run_sweeper(data_access.clone(), retention.clone()));
+    }
+
     // Build our application with the external handler function
-    let app = Router::new()
-        .route("/", axum::routing::get(root_handler))
-        .route("/echo", axum::routing::post(echo_handler))
-        .route("/log", axum::routing::post(log_handler))
-        .route("/report", axum::routing::get(report_handler))
-        .route("/purge", axum::routing::post(purge_handler))
-        .route("/purge", axum::routing::get(purge_handler));
+    let app = app(AppState { data_access, retention: Arc::new(retention), health });
 
-    // Listen on a specified address
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
-    let listener = TcpListener::bind(&addr).await.unwrap();
+    // Listen on the configured address
+    let listener = TcpListener::bind(&config.bind).await.unwrap();
 
-    println!("Listening on http://{}", addr);
+    println!("Listening on http://{}", config.bind);
 
     // Serve the application
     axum::serve(listener, app).await.unwrap();
 }
 
+// Every route of the service over the shared state
+fn app(state: AppState) -> Router {
+    Router::new()
+        .route("/", axum::routing::get(root_handler))
+        .route("/healthz", axum::routing::get(healthz_handler))
+        .route("/readyz", axum::routing::get(readyz_handler))
+        .route("/echo", axum::routing::post(echo_handler))
+        .route("/log", axum::routing::post(log_handler))
+        .route("/log/batch", axum::routing::post(log_batch_handler))
+        .route("/report", axum::routing::get(report_handler))
+        .route("/export", axum::routing::get(export_handler))
+        .route("/stats", axum::routing::get(stats_handler))
+        .route("/readings/{location}/{sensor}/{recorded}", axum::routing::get(get_reading_handler).delete(delete_reading_handler))
+        .route("/purge", axum::routing::post(purge_handler))
+        .route("/purge", axum::routing::get(purge_handler))
+        .route("/admin/retention", axum::routing::get(retention_handler))
+        .layer(DefaultBodyLimit::max(BODY_LIMIT))
+        .with_state(state)
+}
+
 // The external handler function
 pub async fn root_handler() -> impl IntoResponse {
     axum::response::Json(serde_json::json!({"message": "axum api server is running"}))
 }
 
-pub async fn echo_handler(body: Bytes) -> impl IntoResponse {
-    let json_result = serde_json::from_slice::<serde_json::Value>(&body);
-    if let Ok(json) = &json_result {
-        println!("{}", json);
-    }
-    match json_result {
-        Ok(json) => axum::response::Json(json),
-        Err(_) => axum::response::Json(serde_json::json!({"error": "Invalid JSON"})),
-    }
+// Liveness: the process is up and serving, whatever the state of its backends
+pub async fn healthz_handler() -> impl IntoResponse {
+    axum::response::Json(serde_json::json!({"status": "ok"}))
 }
 
-pub async fn log_handler(body: Bytes) -> impl IntoResponse {
-    let json_result = serde_json::from_slice::<serde_json::Value>(&body);
-    match json_result {
-        Ok(json) => {
-            let json_data = json.to_string();
-            let sensor_data_access = get_data_access();
-
-            match sensor_data_access.log_sensor_data(&json_data).await {
-                Ok(result) => {
-                    match result {
-                        Ok(_) => axum::response::Json(serde_json::json!({
-                            "message": "Data logged successfully"
-                        })),
-                        Err(e) => axum::response::Json(serde_json::json!({
-                            "error": format!("Failed to log sensor data: {}", e)
-                        })),
-                    }
-                }
-                Err(e) => axum::response::Json(serde_json::json!({
-                    "error": format!("Task join error: {}", e)
-                })),
-            }
-        }
-        Err(_) => axum::response::Json(serde_json::json!({"error": "Invalid JSON"})),
+// Readiness: the backends answer and their migrations are applied, with each one's latency
+pub async fn readyz_handler(State(health): State<Arc<HealthCheck>>) -> Response {
+    let readiness = health.readiness().await;
+    let status = if readiness.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, axum::response::Json(readiness)).into_response()
+}
+
+pub async fn echo_handler(body: Result<Bytes, BytesRejection>) -> Result<impl IntoResponse, Problem> {
+    let json = serde_json::from_slice::<serde_json::Value>(&body?).map_err(Problem::invalid_json)?;
+    println!("{}", json);
+    Ok(axum::response::Json(json))
+}
+
+pub async fn log_handler(State(sensor_data_access): State<DataAccess>, body: Result<Bytes, BytesRejection>) -> Result<impl IntoResponse, Problem> {
+    let json = serde_json::from_slice::<serde_json::Value>(&body?).map_err(Problem::invalid_json)?;
+    let reading = SensorReading::from_json(&json.to_string()).map_err(Problem::invalid_reading)?;
+
+    let outcome = sensor_data_access.log_sensor_data(&reading).await
+        .map_err(|e| Problem::storage("Failed to log sensor data", e))?;
+    Ok(axum::response::Json(log_response(outcome)))
+}
+
+// A retried post still succeeds, so devices can tell a duplicate apart without treating it as an error
+fn log_response(outcome: LogOutcome) -> serde_json::Value {
+    let message = match outcome {
+        LogOutcome::Created => "Data logged successfully",
+        LogOutcome::Duplicate => "Data already logged",
+    };
+    serde_json::json!({
+        "message": message,
+        "outcome": outcome
+    })
+}
+
+// Accepts a JSON array or NDJSON of readings and reports each one by its index
+pub async fn log_batch_handler(State(sensor_data_access): State<DataAccess>, body: Result<Bytes, BytesRejection>) -> Result<impl IntoResponse, Problem> {
+    let items = parse_batch(&body?).map_err(Problem::bad_request)?;
+    let report = log_batch(&*sensor_data_access, items).await
+        .map_err(|e| Problem::storage("Failed to log sensor data", e))?;
+    Ok(axum::response::Json(report))
+}
+
+// `Link` header pointing at the page after this one, in the same format
+fn next_link(query: &SensorQuery, format: &ReportFormat, next: &str) -> String {
+    let mut params = serde_urlencoded::to_string(query.next_page(next)).unwrap_or_default();
+    if let Some(format) = &format.format {
+        params.push('&');
+        params.push_str(&serde_urlencoded::to_string([("format", format)]).unwrap_or_default());
     }
+    format!("</report?{}>; rel=\"next\"", params)
 }
 
-pub async fn report_handler() -> Response {
-    let sensor_data_access = get_data_access();
-
-    match sensor_data_access.fetch_sensor_data().await {
-        Ok(result) => {
-            match result {
-                Ok(json_strings) => {
-                    if json_strings.is_empty() {
-                        return Response::builder()
-                            .status(StatusCode::OK)
-                            .header(header::CONTENT_TYPE, "text/csv")
-                            .body(axum::body::Body::from("No data available"))
-                            .unwrap();
-                    }
-                    let json_str = &json_strings[0];
-                    match json_to_csv(json_str) {
-                        Ok(csv) => Response::builder()
-                            .status(StatusCode::OK)
-                            .header(header::CONTENT_TYPE, "text/csv")
-                            .header(
-                                header::CONTENT_DISPOSITION,
-                                HeaderValue::from_str("attachment; filename=\"report.csv\"").unwrap(),
-                            )
-                            .body(axum::body::Body::from(csv))
-                            .unwrap(),
-                        Err(e) => Response::builder()
-                            .status(StatusCode::INTERNAL_SERVER_ERROR)
-                            .body(axum::body::Body::from(format!("Failed to convert to CSV: {}", e)))
-                            .unwrap(),
-                    }
-                }
-                Err(e) => Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(axum::body::Body::from(format!("Failed to fetch sensor data: {}", e)))
-                    .unwrap(),
-            }
-        }
-        Err(e) => Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(axum::body::Body::from(format!("Task join error: {}", e)))
-            .unwrap(),
+// Headers shared by streamed and paged reports
+fn report_response(writer: &dyn ReportWriter) -> axum::http::response::Builder {
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, writer.content_type())
+        .header(header::VARY, "Accept");
+    if writer.attachment() {
+        builder = builder.header(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_str(&format!("attachment; filename=\"report.{}\"", writer.name())).unwrap(),
+        );
     }
+    builder
 }
 
-pub async fn purge_handler() -> impl IntoResponse {
-    let sensor_data_access = get_data_access();
+pub async fn report_handler(
+    State(sensor_data_access): State<DataAccess>,
+    headers: HeaderMap,
+    query: Result<Query<SensorQuery>, QueryRejection>,
+    format: Result<Query<ReportFormat>, QueryRejection>,
+) -> Result<Response, Problem> {
+    let (Query(query), Query(format)) = (query?, format?);
+    query.validate().map_err(Problem::bad_request)?;
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let writer = negotiate(format.format.as_deref(), accept)?;
 
-    match sensor_data_access.purge_sensor_data().await {
-        Ok(result) => {
-            match result {
-                Ok(_) => axum::response::Json(serde_json::json!({
-                    "message": "purged"
-                })),
-                Err(e) => axum::response::Json(serde_json::json!({
-                    "error": format!("Failed to purge sensor data: {}", e)
-                })),
-            }
-        }
-        Err(e) => axum::response::Json(serde_json::json!({
-            "error": format!("Task join error: {}", e)
-        })),
+    // Without a page limit, stream the whole report with chunked transfer encoding
+    if query.limit.is_none() {
+        let mut rows = sensor_data_access.stream_sensor_data(&query);
+        let first = first_reading(&mut rows).await
+            .map_err(|e| Problem::storage("Failed to fetch sensor data", e))?;
+        return Ok(report_response(writer)
+            .body(axum::body::Body::from_stream(report_stream(writer, first, rows)))
+            .unwrap());
+    }
+
+    let page = sensor_data_access.fetch_sensor_data(&query).await
+        .map_err(|e| Problem::storage("Failed to fetch sensor data", e))?;
+    let mut builder = report_response(writer);
+    if let Some(next) = &page.next {
+        builder = builder.header(header::LINK, next_link(&query, &format, next));
     }
+    Ok(builder
+        .body(axum::body::Body::from(render_report(writer, &page.readings)))
+        .unwrap())
+}
+
+// Parquet or Arrow IPC file of every reading matching the query's filters, up to MAX_EXPORT_READINGS
+pub async fn export_handler(
+    State(sensor_data_access): State<DataAccess>,
+    query: Result<Query<SensorQuery>, QueryRejection>,
+    format: Result<Query<ReportFormat>, QueryRejection>,
+) -> Result<Response, Problem> {
+    let (Query(query), Query(format)) = (query?, format?);
+    query.validate().map_err(Problem::bad_request)?;
+    let format = ExportFormat::parse(format.format.as_deref()).map_err(Problem::bad_request)?;
+
+    let readings = collect_readings(sensor_data_access.stream_sensor_data(&query), MAX_EXPORT_READINGS).await
+        .map_err(|e| Problem::storage("Failed to fetch sensor data", e))?;
+    let file = export_readings(format, &readings)
+        .map_err(|e| Problem::internal(format!("Failed to export sensor data: {}", e)))?;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_str(&format!("attachment; filename=\"sensor_data.{}\"", format.extension())).unwrap(),
+        )
+        .body(axum::body::Body::from(file))
+        .unwrap())
+}
+
+// Count, min, max and average per time bucket and series of every reading matching the query's filters
+pub async fn stats_handler(
+    State(sensor_data_access): State<DataAccess>,
+    query: Result<Query<SensorQuery>, QueryRejection>,
+    params: Result<Query<StatsParams>, QueryRejection>,
+) -> Result<impl IntoResponse, Problem> {
+    let (Query(query), Query(params)) = (query?, params?);
+    let bucket = Bucket::parse(params.bucket.as_deref()).map_err(Problem::bad_request)?;
+
+    let results = sensor_data_access.aggregate_sensor_data(&query, bucket).await
+        .map_err(|e| Problem::storage("Failed to aggregate sensor data", e))?;
+    Ok(axum::response::Json(serde_json::json!({
+        "bucket": bucket.seconds(),
+        "stats": results
+    })))
+}
+
+pub async fn get_reading_handler(
+    State(sensor_data_access): State<DataAccess>,
+    key: Result<Path<ReadingKey>, PathRejection>,
+) -> Result<impl IntoResponse, Problem> {
+    let Path(key) = key?;
+    let reading = sensor_data_access.get_sensor_reading(&key).await
+        .map_err(|e| Problem::storage("Failed to fetch sensor reading", e))?;
+    Ok(axum::response::Json(reading))
+}
+
+pub async fn delete_reading_handler(
+    State(sensor_data_access): State<DataAccess>,
+    key: Result<Path<ReadingKey>, PathRejection>,
+) -> Result<impl IntoResponse, Problem> {
+    let Path(key) = key?;
+    sensor_data_access.delete_sensor_reading(&key).await
+        .map_err(|e| Problem::storage("Failed to delete sensor reading", e))?;
+    Ok(axum::response::Json(serde_json::json!({
+        "message": "deleted"
+    })))
+}
+
+pub async fn purge_handler(State(sensor_data_access): State<DataAccess>) -> Result<impl IntoResponse, Problem> {
+    sensor_data_access.purge_sensor_data().await
+        .map_err(|e| Problem::storage("Failed to purge sensor data", e))?;
+    Ok(axum::response::Json(serde_json::json!({
+        "message": "purged"
+    })))
+}
+
+// The retention windows in seconds and how the configured backend enforces them
+pub async fn retention_handler(
+    State(sensor_data_access): State<DataAccess>,
+    State(retention): State<Arc<RetentionPolicy>>,
+) -> Response {
+    axum::response::Json(retention.report(sensor_data_access.expires_natively())).into_response()
 }
@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use sensor_store::report_writer::NegotiationError;
+use sensor_store::sensor_data_json_helper::ValidationErrors;
+use sensor_store::storage_error::StorageError;
+use axum::extract::rejection::{BytesRejection, PathRejection, QueryRejection};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+/// Largest request body accepted, the same in the axum and actix apps.
+pub const BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// An RFC 9457 problem details body, served as `application/problem+json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    /// Field-level problems of a rejected sensor reading.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<ValidationErrors>,
+}
+
+impl Problem {
+    /// A problem whose title is the status code's reason phrase, as `about:blank` requires.
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Problem {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Unknown Status"),
+            status: status.as_u16(),
+            detail: detail.into(),
+            errors: None,
+        }
+    }
+
+    pub fn bad_request(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::BAD_REQUEST, detail)
+    }
+
+    pub fn internal(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::INTERNAL_SERVER_ERROR, detail)
+    }
+
+    pub fn invalid_json(e: serde_json::Error) -> Self {
+        Problem::bad_request(format!("Invalid JSON: {}", e))
+    }
+
+    pub fn payload_too_large() -> Self {
+        Problem::new(StatusCode::PAYLOAD_TOO_LARGE, format!("Request body is larger than {} bytes", BODY_LIMIT))
+    }
+
+    /// Well-formed JSON that is not a valid sensor reading.
+    pub fn invalid_reading(errors: ValidationErrors) -> Self {
+        Problem {
+            errors: Some(errors),
+            ..Problem::new(StatusCode::UNPROCESSABLE_ENTITY, "Invalid sensor data")
+        }
+    }
+
+    /// A failed storage operation; unreachable or slow backends are reported as unavailable.
+    pub fn storage(context: &str, e: StorageError) -> Self {
+        let status = match &e {
+            StorageError::Connection(_) | StorageError::Timeout(_) => StatusCode::SERVICE_UNAVAILABLE,
+            StorageError::Validation(_) => StatusCode::BAD_REQUEST,
+            StorageError::NotFound(_) => StatusCode::NOT_FOUND,
+            StorageError::Conflict(_) => StatusCode::CONFLICT,
+            StorageError::Backend(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Problem::new(status, format!("{}: {}", context, e))
+    }
+}
+
+impl From<NegotiationError> for Problem {
+    fn from(e: NegotiationError) -> Self {
+        let status = match e {
+            NegotiationError::UnknownFormat(_) => StatusCode::BAD_REQUEST,
+            NegotiationError::NotAcceptable => StatusCode::NOT_ACCEPTABLE,
+        };
+        Problem::new(status, e.to_string())
+    }
+}
+
+impl From<BytesRejection> for Problem {
+    fn from(rejection: BytesRejection) -> Self {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            Problem::payload_too_large()
+        } else {
+            Problem::bad_request(rejection.body_text())
+        }
+    }
+}
+
+impl From<QueryRejection> for Problem {
+    fn from(rejection: QueryRejection) -> Self {
+        Problem::bad_request(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for Problem {
+    fn from(rejection: PathRejection) -> Self {
+        Problem::bad_request(rejection.body_text())
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_vec(&self).expect("Problem is always serializable");
+        (status, [(header::CONTENT_TYPE, PROBLEM_JSON)], body).into_response()
+    }
+}
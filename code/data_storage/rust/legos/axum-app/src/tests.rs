@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use super::{app, AppState, DataAccess};
+use crate::config::{JsonlConfig, SqliteConfig};
+use sensor_store::fanout_data_access::WritePolicy;
+use sensor_store::health::HealthCheck;
+use sensor_store::jsonl_data_access::JsonlDataAccess;
+use sensor_store::memory_data_access::MemoryDataAccess;
+use sensor_store::migrations::migrate;
+use sensor_store::retention::RetentionPolicy;
+use sensor_store::sensor_data_access_trait::{DynSensorDataAccess, LogOutcome, SensorDataAccess, SensorReadingStream};
+use sensor_store::sensor_query::{SensorPage, SensorQuery};
+use sensor_store::sensor_reading::{ReadingKey, SensorReading};
+use sensor_store::sqlite_data_access::SqliteDataAccess;
+use sensor_store::storage_error::{StorageError, StorageResult};
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+
+// The whole service over an empty in-memory backend
+fn test_app() -> Router {
+    app_over(Arc::new(MemoryDataAccess::new()))
+}
+
+fn app_over(data_access: DataAccess) -> Router {
+    app(AppState {
+        health: Arc::new(HealthCheck::new(vec![("memory".to_string(), data_access.clone())], WritePolicy::All)),
+        data_access,
+        retention: Arc::new(RetentionPolicy::default()),
+    })
+}
+
+// A backend whose server is down: every call fails as an unreachable connection would
+struct DownDataAccess;
+
+fn down() -> StorageError {
+    StorageError::Connection("connection refused".to_string())
+}
+
+impl SensorDataAccess for DownDataAccess {
+    async fn log_sensor_data(&self, _reading: &SensorReading) -> StorageResult<LogOutcome> {
+        Err(down())
+    }
+
+    async fn log_sensor_data_batch(&self, _readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        Err(down())
+    }
+
+    async fn fetch_sensor_data(&self, _query: &SensorQuery) -> StorageResult<SensorPage> {
+        Err(down())
+    }
+
+    fn stream_sensor_data(&self, _query: &SensorQuery) -> SensorReadingStream {
+        let (tx, rx) = mpsc::channel(1);
+        tx.try_send(Err(down())).unwrap();
+        rx
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        Err(down())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        Err(down())
+    }
+
+    async fn get_sensor_reading(&self, _key: &ReadingKey) -> StorageResult<SensorReading> {
+        Err(down())
+    }
+
+    async fn delete_sensor_reading(&self, _key: &ReadingKey) -> StorageResult<()> {
+        Err(down())
+    }
+}
+
+// An hour boundary in 2023; readings are recorded at offsets from it
+const T0: i64 = 1_699_999_200;
+
+fn reading(location: &str, offset: i64, value: f64) -> Value {
+    json!({
+        "recorded": T0 + offset,
+        "location": location,
+        "sensor": "bmp280",
+        "measurement": "temperature",
+        "units": "C",
+        "value": value
+    })
+}
+
+// Send one request and return the status, headers and body parsed as JSON
+async fn send(app: &Router, method: Method, uri: &str, body: Option<String>) -> (StatusCode, header::HeaderMap, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body.map_or_else(Body::empty, Body::from))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn log(app: &Router, body: &Value) -> (StatusCode, Value) {
+    let (status, _, body) = send(app, Method::POST, "/log", Some(body.to_string())).await;
+    (status, body)
+}
+
+#[tokio::test]
+async fn log_reports_duplicates() {
+    let app = test_app();
+    let (status, body) = log(&app, &reading("kitchen", 0, 21.5)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["outcome"], "created");
+
+    let (status, body) = log(&app, &reading("kitchen", 0, 99.0)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["outcome"], "duplicate");
+
+    let (_, _, body) = send(&app, Method::GET, &format!("/readings/kitchen/bmp280/{}", T0), None).await;
+    assert_eq!(body["value"], 21.5);
+}
+
+#[tokio::test]
+async fn log_rejects_invalid_readings() {
+    let app = test_app();
+    let (status, body) = log(&app, &json!({"location": "kitchen"})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["errors"].is_array() || body["errors"].is_object());
+
+    let (status, _, _) = send(&app, Method::POST, "/log", Some("{".to_string())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn report_orders_by_recorded_and_pages() {
+    let app = test_app();
+    for (location, recorded) in [("kitchen", 30), ("garage", 10), ("kitchen", 20), ("garage", 20)] {
+        log(&app, &reading(location, recorded, 1.0)).await;
+    }
+
+    let (status, _, body) = send(&app, Method::GET, "/report?format=json", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let order: Vec<(&str, i64)> = body.as_array().unwrap()
+        .iter()
+        .map(|r| (r["location"].as_str().unwrap(), r["recorded"].as_i64().unwrap() - T0))
+        .collect();
+    // Ties on `recorded` keep the order the readings were logged in
+    assert_eq!(order, [("garage", 10), ("kitchen", 20), ("garage", 20), ("kitchen", 30)]);
+
+    let (_, headers, body) = send(&app, Method::GET, &format!("/report?format=json&from={}&limit=2", T0 + 15), None).await;
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    let link = headers[header::LINK].to_str().unwrap();
+    let next = link.trim_start_matches('<').split('>').next().unwrap().to_string();
+    let (_, headers, body) = send(&app, Method::GET, &next, None).await;
+    assert_eq!(body[0]["recorded"], T0 + 30);
+    assert!(!headers.contains_key(header::LINK));
+}
+
+#[tokio::test]
+async fn report_and_export_reject_out_of_range_limits() {
+    let app = test_app();
+    log(&app, &reading("kitchen", 10, 1.0)).await;
+
+    // An empty page would link back to itself and a huge one overflows the backends' row counts
+    for path in ["/report?format=json", "/export?format=arrow"] {
+        for limit in ["0", "10001", "18446744073709551615"] {
+            let (status, headers, body) = send(&app, Method::GET, &format!("{}&limit={}", path, limit), None).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{} limit={}", path, limit);
+            assert_eq!(headers[header::CONTENT_TYPE], "application/problem+json");
+            assert!(body["detail"].as_str().unwrap().contains("limit"));
+        }
+        let (status, _, _) = send(&app, Method::GET, &format!("{}&limit=10000", path), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn readings_can_be_deleted() {
+    let app = test_app();
+    log(&app, &reading("kitchen", 10, 1.0)).await;
+
+    let uri = format!("/readings/kitchen/bmp280/{}", T0 + 10);
+    let (status, _, _) = send(&app, Method::DELETE, &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _, _) = send(&app, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = send(&app, Method::DELETE, &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = send(&app, Method::GET, "/readings/kitchen/bmp280/soon", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A deleted key can be logged again
+    let (_, body) = log(&app, &reading("kitchen", 10, 2.0)).await;
+    assert_eq!(body["outcome"], "created");
+}
+
+#[tokio::test]
+async fn purge_removes_everything() {
+    let app = test_app();
+    log(&app, &reading("kitchen", 10, 1.0)).await;
+    log(&app, &reading("garage", 20, 1.0)).await;
+
+    let (status, _, _) = send(&app, Method::POST, "/purge", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, _, body) = send(&app, Method::GET, "/report?format=json", None).await;
+    assert_eq!(body, json!([]));
+
+    let (_, body) = log(&app, &reading("kitchen", 10, 1.0)).await;
+    assert_eq!(body["outcome"], "created");
+}
+
+#[tokio::test]
+async fn batch_reports_each_item() {
+    let app = test_app();
+    log(&app, &reading("kitchen", 10, 1.0)).await;
+    let batch = json!([reading("kitchen", 10, 1.0), {"location": "garage"}, reading("garage", 10, 1.0), reading("garage", 10, 2.0)]);
+
+    let (status, _, body) = send(&app, Method::POST, "/log/batch", Some(batch.to_string())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["accepted"], 3);
+    assert_eq!(body["rejected"], 1);
+    let statuses: Vec<&str> = body["results"].as_array().unwrap()
+        .iter()
+        .map(|r| r["outcome"].as_str().unwrap_or_else(|| r["status"].as_str().unwrap()))
+        .collect();
+    assert_eq!(statuses, ["duplicate", "rejected", "created", "duplicate"]);
+}
+
+#[tokio::test]
+async fn stats_group_by_bucket() {
+    let app = test_app();
+    for (recorded, value) in [(0, 1.0), (1_800, 3.0), (3_600, 5.0)] {
+        log(&app, &reading("kitchen", recorded, value)).await;
+    }
+
+    let (status, _, body) = send(&app, Method::GET, "/stats?bucket=1h", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["bucket"], 3_600);
+    assert_eq!(body["stats"][0]["count"], 2);
+    assert_eq!(body["stats"][0]["avg"], 2.0);
+    assert_eq!(body["stats"][0]["bucket_start"], T0);
+    assert_eq!(body["stats"][1]["bucket_start"], T0 + 3_600);
+
+    let (status, _, _) = send(&app, Method::GET, "/stats?bucket=soon", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn health_and_readiness() {
+    let app = test_app();
+    let (status, _, body) = send(&app, Method::GET, "/healthz", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+
+    let (status, _, body) = send(&app, Method::GET, "/readyz", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["stores"][0]["name"], "memory");
+    assert_eq!(body["stores"][0]["reachable"], true);
+    assert!(body["stores"][0]["latency_ms"].is_number());
+}
+
+#[tokio::test]
+async fn down_backend_is_reported_before_streaming() {
+    let app = app_over(Arc::new(DownDataAccess));
+    let (status, headers, body) = send(&app, Method::GET, "/report?format=json", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(headers[header::CONTENT_TYPE], "application/problem+json");
+    assert!(body["detail"].as_str().unwrap().contains("connection refused"));
+
+    let (status, _, body) = send(&app, Method::GET, "/readyz", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["stores"][0]["reachable"], false);
+}
+
+#[tokio::test]
+async fn expiry_includes_the_boundary() {
+    let dir = std::env::temp_dir().join(format!("axum-app-expiry-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let sqlite = SqliteDataAccess::connect(&SqliteConfig {
+        url: format!("sqlite://{}", dir.join("expiry.db").display()),
+        max_connections: 1,
+    })
+    .await
+    .unwrap();
+    migrate(&sqlite).await.unwrap();
+    // One byte per segment, so each reading gets its own and only the newest is active
+    let jsonl = JsonlDataAccess::connect(&JsonlConfig { dir: dir.join("jsonl").display().to_string(), segment_bytes: 1 })
+        .await
+        .unwrap();
+    let stores: Vec<(&str, Arc<dyn DynSensorDataAccess>)> =
+        vec![("memory", Arc::new(MemoryDataAccess::new())), ("sqlite", Arc::new(sqlite)), ("jsonl", Arc::new(jsonl))];
+
+    let policy = RetentionPolicy { default: Some(3_600), ..Default::default() };
+    for (name, store) in &stores {
+        for offset in [0, 36_000] {
+            let reading: SensorReading = serde_json::from_value(reading("kitchen", offset, 1.0)).unwrap();
+            store.log_sensor_data(&reading).await.unwrap();
+        }
+        // The reading at T0 expires at exactly T0 + 1h
+        assert_eq!(store.expire_sensor_data(&policy, T0 + 3_599).await.unwrap(), 0, "{}", name);
+        assert_eq!(store.expire_sensor_data(&policy, T0 + 3_600).await.unwrap(), 1, "{}", name);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn jsonl_pages_in_time_order_across_segments() {
+    let dir = std::env::temp_dir().join(format!("axum-app-jsonl-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    // Two readings per segment, logged out of order so the first three segments overlap in time
+    let jsonl: Arc<dyn DynSensorDataAccess> = Arc::new(
+        JsonlDataAccess::connect(&JsonlConfig { dir: dir.display().to_string(), segment_bytes: 200 }).await.unwrap(),
+    );
+    for offset in [30, 10, 20, 50, 40, 60, 80, 70] {
+        let reading: SensorReading = serde_json::from_value(reading("kitchen", offset, 1.0)).unwrap();
+        jsonl.log_sensor_data(&reading).await.unwrap();
+    }
+    let key = ReadingKey { location: "kitchen".to_string(), sensor: "bmp280".to_string(), recorded: T0 + 40 };
+    jsonl.delete_sensor_reading(&key).await.unwrap();
+
+    let mut streamed = Vec::new();
+    let mut rows = jsonl.stream_sensor_data(&SensorQuery::default());
+    while let Some(reading) = rows.recv().await {
+        streamed.push(reading.unwrap().recorded - T0);
+    }
+    assert_eq!(streamed, [10, 20, 30, 50, 60, 70, 80]);
+
+    let mut paged = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = jsonl.fetch_sensor_data(&SensorQuery { limit: Some(2), cursor, ..Default::default() }).await.unwrap();
+        paged.extend(page.readings.iter().map(|reading| reading.recorded - T0));
+        cursor = page.next;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(paged, streamed);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+//! Report formats and `Accept` negotiation shared by every app that serves reports.
+//! Writers work on any `ReportRow`, so JSON records and typed readings render the same way.
+
+use sensor_csv::csv_record;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// A JSON object; its keys are the report columns.
+pub type Record = Map<String, Value>;
+
+/// One row of a report.
+pub trait ReportRow {
+    /// The row's values in column order.
+    fn values(&self) -> Vec<Value>;
+    /// The row as a JSON object.
+    fn to_json(&self) -> String;
+}
+
+impl ReportRow for Record {
+    fn values(&self) -> Vec<Value> {
+        Map::values(self).cloned().collect()
+    }
+    fn to_json(&self) -> String {
+        Value::Object(self.clone()).to_string()
+    }
+}
+
+/// A report format. A document is `begin`, then `row` for each row, then `end`,
+/// so the same writer serves both buffered pages and chunked streams.
+pub trait ReportWriter: Sync {
+    /// Short name accepted by the `format=` query parameter; also the file extension.
+    fn name(&self) -> &'static str;
+    /// Value of the `Content-Type` header.
+    fn content_type(&self) -> &'static str;
+    /// Whether browsers should download the report rather than display it.
+    fn attachment(&self) -> bool {
+        false
+    }
+    fn begin(&self, _columns: &[&str]) -> String {
+        String::new()
+    }
+    /// `index` is the row's zero-based position in the document.
+    fn row(&self, index: usize, row: &dyn ReportRow) -> String;
+    fn end(&self) -> String {
+        String::new()
+    }
+}
+
+/// Every supported format; the first is the default when the client expresses no preference.
+pub static REPORT_WRITERS: &[&dyn ReportWriter] = &[
+    &CsvReportWriter,
+    &JsonReportWriter,
+    &NdjsonReportWriter,
+    &TsvReportWriter,
+    &HtmlReportWriter,
+];
+
+/// The `format=` query parameter, which takes precedence over `Accept`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportFormat {
+    pub format: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum NegotiationError {
+    /// `format=` named no known writer.
+    UnknownFormat(String),
+    /// `Accept` matched no writer.
+    NotAcceptable,
+}
+
+impl std::fmt::Display for NegotiationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let supported: Vec<&str> = REPORT_WRITERS.iter().map(|w| w.name()).collect();
+        match self {
+            NegotiationError::UnknownFormat(format) => {
+                write!(f, "Unknown report format '{}'; supported: {}", format, supported.join(", "))
+            }
+            NegotiationError::NotAcceptable => {
+                let types: Vec<&str> = REPORT_WRITERS.iter().map(|w| media_type(w.content_type())).collect();
+                write!(f, "No acceptable report format; available: {}", types.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for NegotiationError {}
+
+/// Pick a writer from the `format=` parameter, else from the `Accept` header.
+pub fn negotiate(format: Option<&str>, accept: Option<&str>) -> Result<&'static dyn ReportWriter, NegotiationError> {
+    if let Some(format) = format {
+        return REPORT_WRITERS
+            .iter()
+            .copied()
+            .find(|w| w.name().eq_ignore_ascii_case(format.trim()))
+            .ok_or_else(|| NegotiationError::UnknownFormat(format.to_string()));
+    }
+    let accept = match accept.map(str::trim) {
+        Some(accept) if !accept.is_empty() => accept,
+        _ => return Ok(REPORT_WRITERS[0]),
+    };
+
+    // Media ranges in preference order: highest q first, ties kept in header order
+    let mut ranges: Vec<(String, f32)> = accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let media = parts.next()?.trim().to_ascii_lowercase();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!media.is_empty() && q > 0.0).then_some((media, q))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranges
+        .iter()
+        .find_map(|(range, _)| REPORT_WRITERS.iter().copied().find(|w| media_matches(range, w.content_type())))
+        .ok_or(NegotiationError::NotAcceptable)
+}
+
+fn media_type(content_type: &str) -> &str {
+    content_type.split(';').next().unwrap_or_default().trim()
+}
+
+fn media_matches(range: &str, content_type: &str) -> bool {
+    let media = media_type(content_type);
+    match range.strip_suffix("/*") {
+        Some("*") => true,
+        Some(kind) => media.split('/').next() == Some(kind),
+        None => range == media,
+    }
+}
+
+/// Column names for records: the first record's keys.
+pub fn record_columns(records: &[Record]) -> Vec<&str> {
+    records.first().map(|r| r.keys().map(String::as_str).collect()).unwrap_or_default()
+}
+
+/// A complete document for the rows under the given columns.
+pub fn render_report<R: ReportRow>(writer: &dyn ReportWriter, columns: &[&str], rows: &[R]) -> String {
+    let mut body = writer.begin(columns);
+    for (index, row) in rows.iter().enumerate() {
+        body.push_str(&writer.row(index, row));
+    }
+    body.push_str(&writer.end());
+    body
+}
+
+// Field text for the delimited and HTML formats: strings unquoted, everything else as JSON
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// `text/csv` through the shared RFC 4180 writer.
+pub struct CsvReportWriter;
+
+impl ReportWriter for CsvReportWriter {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+    fn content_type(&self) -> &'static str {
+        "text/csv"
+    }
+    fn attachment(&self) -> bool {
+        true
+    }
+    fn begin(&self, columns: &[&str]) -> String {
+        csv_record(columns)
+    }
+    fn row(&self, _index: usize, row: &dyn ReportRow) -> String {
+        csv_record(row.values().iter().map(field_text))
+    }
+}
+
+/// A single JSON array of row objects.
+pub struct JsonReportWriter;
+
+impl ReportWriter for JsonReportWriter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+    fn begin(&self, _columns: &[&str]) -> String {
+        "[".to_string()
+    }
+    fn row(&self, index: usize, row: &dyn ReportRow) -> String {
+        let separator = if index == 0 { "" } else { "," };
+        format!("{}{}", separator, row.to_json())
+    }
+    fn end(&self) -> String {
+        "]".to_string()
+    }
+}
+
+/// Newline-delimited JSON, one row object per line.
+pub struct NdjsonReportWriter;
+
+impl ReportWriter for NdjsonReportWriter {
+    fn name(&self) -> &'static str {
+        "ndjson"
+    }
+    fn content_type(&self) -> &'static str {
+        "application/x-ndjson"
+    }
+    fn row(&self, _index: usize, row: &dyn ReportRow) -> String {
+        format!("{}\n", row.to_json())
+    }
+}
+
+/// Tab-separated values; tabs, line breaks and backslashes inside fields are backslash-escaped.
+pub struct TsvReportWriter;
+
+fn tsv_escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+impl ReportWriter for TsvReportWriter {
+    fn name(&self) -> &'static str {
+        "tsv"
+    }
+    fn content_type(&self) -> &'static str {
+        "text/tab-separated-values"
+    }
+    fn attachment(&self) -> bool {
+        true
+    }
+    fn begin(&self, columns: &[&str]) -> String {
+        let columns: Vec<String> = columns.iter().map(|c| tsv_escape(c)).collect();
+        format!("{}\n", columns.join("\t"))
+    }
+    fn row(&self, _index: usize, row: &dyn ReportRow) -> String {
+        let fields: Vec<String> = row.values().iter().map(|v| tsv_escape(&field_text(v))).collect();
+        format!("{}\n", fields.join("\t"))
+    }
+}
+
+/// A standalone HTML page holding the rows in a styled table.
+pub struct HtmlReportWriter;
+
+fn html_escape(field: &str) -> String {
+    field
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+impl ReportWriter for HtmlReportWriter {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+    fn content_type(&self) -> &'static str {
+        "text/html; charset=utf-8"
+    }
+    fn begin(&self, columns: &[&str]) -> String {
+        let headings: String = columns.iter().map(|c| format!("<th>{}</th>", html_escape(c))).collect();
+        format!(
+            concat!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Sensor report</title>\n",
+                "<style>\n",
+                "body {{ font-family: sans-serif; margin: 2em; }}\n",
+                "table {{ border-collapse: collapse; }}\n",
+                "th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; }}\n",
+                "th {{ background: #f0f0f0; text-align: left; }}\n",
+                "tr:nth-child(even) td {{ background: #fafafa; }}\n",
+                "td.number {{ text-align: right; }}\n",
+                "</style>\n</head>\n<body>\n<table>\n<thead>\n<tr>{}</tr>\n</thead>\n<tbody>\n"
+            ),
+            headings
+        )
+    }
+    fn row(&self, _index: usize, row: &dyn ReportRow) -> String {
+        let cells: String = row
+            .values()
+            .iter()
+            .map(|v| match v {
+                Value::Number(_) => format!("<td class=\"number\">{}</td>", v),
+                _ => format!("<td>{}</td>", html_escape(&field_text(v))),
+            })
+            .collect();
+        format!("<tr>{}</tr>\n", cells)
+    }
+    fn end(&self) -> String {
+        "</tbody>\n</table>\n</body>\n</html>\n".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(json: &str) -> Record {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn format_parameter_wins_over_accept() {
+        assert_eq!(negotiate(Some("TSV"), Some("application/json")).unwrap().name(), "tsv");
+        assert_eq!(negotiate(Some("xml"), None).err(), Some(NegotiationError::UnknownFormat("xml".into())));
+    }
+
+    #[test]
+    fn accept_is_ranked_by_q_value() {
+        assert_eq!(negotiate(None, None).unwrap().name(), "csv");
+        assert_eq!(negotiate(None, Some("text/csv;q=0.5, application/json")).unwrap().name(), "json");
+        assert_eq!(negotiate(None, Some("text/*;q=0.9, application/x-ndjson;q=0.1")).unwrap().name(), "csv");
+        assert_eq!(negotiate(None, Some("image/png, text/csv;q=0")).err(), Some(NegotiationError::NotAcceptable));
+    }
+
+    #[test]
+    fn records_render_under_their_first_keys() {
+        let records = [record(r#"{"location":"den, east","value":21.5}"#), record(r#"{"location":"attic","value":-3}"#)];
+        let columns = record_columns(&records);
+        assert_eq!(render_report(&CsvReportWriter, &columns, &records), "location,value\r\n\"den, east\",21.5\r\nattic,-3\r\n");
+        assert_eq!(
+            render_report(&JsonReportWriter, &columns, &records),
+            r#"[{"location":"den, east","value":21.5},{"location":"attic","value":-3}]"#
+        );
+        assert_eq!(render_report(&TsvReportWriter, &columns, &records[1..]), "location\tvalue\nattic\t-3\n");
+    }
+}
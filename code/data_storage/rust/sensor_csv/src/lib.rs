@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+//! The RFC 4180 writer shared by every crate that produces CSV: CRLF line endings,
+//! fields quoted only when they hold a comma, quote or line break, and embedded quotes doubled.
+
+use csv::{Terminator, Writer, WriterBuilder};
+use serde::Serialize;
+
+fn writer() -> Writer<Vec<u8>> {
+    WriterBuilder::new()
+        .has_headers(false)
+        .terminator(Terminator::CRLF)
+        .from_writer(Vec::new())
+}
+
+fn finish(writer: Writer<Vec<u8>>) -> String {
+    let bytes = writer.into_inner().expect("writing CSV to memory cannot fail");
+    String::from_utf8(bytes).expect("CSV built from strings is valid UTF-8")
+}
+
+/// One line holding the fields in order, such as a header.
+pub fn csv_record<I, T>(fields: I) -> String
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = writer();
+    writer.write_record(fields).expect("writing CSV to memory cannot fail");
+    finish(writer)
+}
+
+/// One line per row, each holding the row's fields in declaration order.
+pub fn csv_rows<S: Serialize>(rows: &[S]) -> String {
+    let mut writer = writer();
+    for row in rows {
+        writer.serialize(row).expect("writing CSV to memory cannot fail");
+    }
+    finish(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        location: &'static str,
+        value: f64,
+    }
+
+    #[test]
+    fn plain_fields_are_not_quoted() {
+        assert_eq!(csv_record(["recorded", "location", "value"]), "recorded,location,value\r\n");
+    }
+
+    #[test]
+    fn commas_quotes_and_line_breaks_are_escaped() {
+        assert_eq!(
+            csv_record(["den, east", "the \"big\" one", "line\nbreak", "cr\rlf"]),
+            "\"den, east\",\"the \"\"big\"\" one\",\"line\nbreak\",\"cr\rlf\"\r\n"
+        );
+    }
+
+    #[test]
+    fn rows_end_with_crlf_in_field_order() {
+        let rows = [Row { location: "den", value: 21.5 }, Row { location: "attic, north", value: -3.0 }];
+        assert_eq!(csv_rows(&rows), "den,21.5\r\n\"attic, north\",-3.0\r\n");
+        assert_eq!(csv_rows::<Row>(&[]), "");
+    }
+}
@@ -0,0 +1,13 @@
+-- SPDX-License-Identifier: GPL-3.0-or-later
+-- Copyright (C) 2025-2026 ggeoffre, LLC
+
+-- IF NOT EXISTS adopts tables created before migrations were tracked
+CREATE TABLE IF NOT EXISTS sensor_data (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    recorded BIGINT NOT NULL,
+    location VARCHAR(255) NOT NULL,
+    sensor VARCHAR(255) NOT NULL,
+    measurement VARCHAR(255) NOT NULL,
+    units VARCHAR(50) NOT NULL,
+    value DOUBLE NOT NULL
+);
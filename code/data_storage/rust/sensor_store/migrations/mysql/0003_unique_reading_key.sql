@@ -0,0 +1,13 @@
+-- SPDX-License-Identifier: GPL-3.0-or-later
+-- Copyright (C) 2025-2026 ggeoffre, LLC
+
+-- Keep the first copy of each reading logged before the key was enforced
+DELETE newer FROM sensor_data newer
+JOIN sensor_data older
+    ON newer.location = older.location
+    AND newer.sensor = older.sensor
+    AND newer.recorded = older.recorded
+    AND newer.id > older.id;
+
+-- A retried post of the same reading is rejected instead of stored twice
+CREATE UNIQUE INDEX uq_sensor_data_reading ON sensor_data (location, sensor, recorded);
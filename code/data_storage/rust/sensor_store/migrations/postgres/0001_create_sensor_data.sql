@@ -0,0 +1,13 @@
+-- SPDX-License-Identifier: GPL-3.0-or-later
+-- Copyright (C) 2025-2026 ggeoffre, LLC
+
+-- IF NOT EXISTS adopts tables created before migrations were tracked
+CREATE TABLE IF NOT EXISTS sensor_data (
+    id SERIAL PRIMARY KEY,
+    recorded BIGINT NOT NULL,
+    location VARCHAR NOT NULL,
+    sensor VARCHAR NOT NULL,
+    measurement VARCHAR NOT NULL,
+    units VARCHAR NOT NULL,
+    value DOUBLE PRECISION NOT NULL
+);
@@ -0,0 +1,5 @@
+-- SPDX-License-Identifier: GPL-3.0-or-later
+-- Copyright (C) 2025-2026 ggeoffre, LLC
+
+-- Serves time ranges and keyset pages ordered by (recorded, id)
+CREATE INDEX IF NOT EXISTS idx_sensor_data_recorded ON sensor_data (recorded, id);
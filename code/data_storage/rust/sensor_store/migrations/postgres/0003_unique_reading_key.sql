@@ -0,0 +1,13 @@
+-- SPDX-License-Identifier: GPL-3.0-or-later
+-- Copyright (C) 2025-2026 ggeoffre, LLC
+
+-- Keep the first copy of each reading logged before the key was enforced
+DELETE FROM sensor_data newer
+USING sensor_data older
+WHERE newer.location = older.location
+    AND newer.sensor = older.sensor
+    AND newer.recorded = older.recorded
+    AND newer.id > older.id;
+
+-- The conflict target of ON CONFLICT in log_sensor_data
+CREATE UNIQUE INDEX IF NOT EXISTS uq_sensor_data_reading ON sensor_data (location, sensor, recorded);
@@ -0,0 +1,137 @@
+-- SPDX-License-Identifier: GPL-3.0-or-later
+-- Copyright (C) 2025-2026 ggeoffre, LLC
+
+-- KEYS[1] is the sorted set scored by `recorded`; KEYS[2] is the list readings were pushed to before it;
+-- KEYS[3] is the quarantine list.
+-- Move the list into the set, keeping the first logged copy of each reading, then drop the list.
+-- LPUSH kept the newest reading at the head, so walk the list from its tail.
+-- Entries that `validate_sensor_json` would reject are pushed to the quarantine list unchanged,
+-- so one bad entry neither stops the move nor lands in the set.
+local MIN_RECORDED = 946684800
+local MAX_RECORDED = 4102444800
+
+local function trim(s)
+    return s:match('^%s*(.-)%s*$')
+end
+
+-- Integers, or integer strings as older devices sent them
+local function as_integer(v)
+    if type(v) == 'number' and v == math.floor(v) then
+        return v
+    elseif type(v) == 'string' and trim(v):match('^[-+]?%d+$') then
+        return tonumber(trim(v))
+    end
+end
+
+-- Numbers, or numeric strings as older devices sent them; Lua also reads hex, which Rust does not
+local function as_number(v)
+    if type(v) == 'number' then
+        return v
+    elseif type(v) == 'string' and not v:find('[xX]') then
+        return tonumber(trim(v))
+    end
+end
+
+local function is_finite(n)
+    return n == n and n ~= math.huge and n ~= -math.huge
+end
+
+-- Characters rather than bytes: count every byte that does not continue a UTF-8 sequence
+local function char_count(s)
+    return select(2, s:gsub('[^\128-\191]', ''))
+end
+
+local function is_valid(reading)
+    if type(reading) ~= 'table' then
+        return false
+    end
+    local recorded = as_integer(reading.recorded)
+    if not recorded or recorded < MIN_RECORDED or recorded > MAX_RECORDED then
+        return false
+    end
+    for field, max_len in pairs({ location = 255, sensor = 255, measurement = 255, units = 50 }) do
+        local v = reading[field]
+        if type(v) ~= 'string' or trim(v) == '' or char_count(v) > max_len then
+            return false
+        end
+    end
+    local value = as_number(reading.value)
+    return value ~= nil and is_finite(value)
+end
+
+local escapes = { ['"'] = '\\"', ['\\'] = '\\\\', ['\b'] = '\\b', ['\f'] = '\\f', ['\n'] = '\\n', ['\r'] = '\\r', ['\t'] = '\\t' }
+
+-- A JSON string as serde_json writes it; cjson would also escape '/'
+local function string_json(s)
+    local escaped = s:gsub('[%z\1-\31"\\]', function(c)
+        return escapes[c] or string.format('\\u%04x', c:byte())
+    end)
+    return '"' .. escaped .. '"'
+end
+
+-- A finite number as serde_json writes it: the shortest digits that read back as the same number,
+-- written out for 1e-5 <= |n| < 1e16 and in exponent form otherwise
+local function number_json(n)
+    local s
+    for precision = 0, 16 do
+        s = string.format('%.' .. precision .. 'e', n)
+        if tonumber(s) == n then
+            break
+        end
+    end
+    local sign, first, rest, exponent = s:match('^(-?)(%d)%.?(%d*)e([-+]%d+)$')
+    if n == 0 then
+        return sign .. '0.0'
+    end
+    local digits = (first .. rest):gsub('0+$', '')
+    local point = tonumber(exponent) + 1
+    if point > 0 and point <= 16 then
+        if #digits <= point then
+            return sign .. digits .. string.rep('0', point - #digits) .. '.0'
+        end
+        return sign .. digits:sub(1, point) .. '.' .. digits:sub(point + 1)
+    elseif point > -5 and point <= 0 then
+        return sign .. '0.' .. string.rep('0', -point) .. digits
+    end
+    local mantissa = #digits == 1 and digits or digits:sub(1, 1) .. '.' .. digits:sub(2)
+    return sign .. mantissa .. string.format('e%+d', point - 1)
+end
+
+-- The member `SensorReading::to_json` writes for the reading
+local function canonical(reading, recorded)
+    return '{"recorded":' .. string.format('%d', recorded)
+        .. ',"location":' .. string_json(reading.location)
+        .. ',"sensor":' .. string_json(reading.sensor)
+        .. ',"measurement":' .. string_json(reading.measurement)
+        .. ',"units":' .. string_json(reading.units)
+        .. ',"value":' .. number_json(as_number(reading.value)) .. '}'
+end
+
+local chunk = 1000
+local moved = 0
+local quarantined = 0
+for stop = redis.call('LLEN', KEYS[2]) - 1, 0, -chunk do
+    local members = redis.call('LRANGE', KEYS[2], math.max(stop - chunk + 1, 0), stop)
+    for i = #members, 1, -1 do
+        local ok, reading = pcall(cjson.decode, members[i])
+        if not ok or not is_valid(reading) then
+            redis.call('RPUSH', KEYS[3], members[i])
+            quarantined = quarantined + 1
+        else
+            local recorded = as_integer(reading.recorded)
+            local duplicate = false
+            for _, existing in ipairs(redis.call('ZRANGEBYSCORE', KEYS[1], recorded, recorded)) do
+                local other = cjson.decode(existing)
+                if other.location == reading.location and other.sensor == reading.sensor then
+                    duplicate = true
+                    break
+                end
+            end
+            if not duplicate then
+                moved = moved + redis.call('ZADD', KEYS[1], recorded, canonical(reading, recorded))
+            end
+        end
+    end
+end
+redis.call('DEL', KEYS[2])
+return { moved, quarantined }
@@ -0,0 +1,13 @@
+-- SPDX-License-Identifier: GPL-3.0-or-later
+-- Copyright (C) 2025-2026 ggeoffre, LLC
+
+-- INTEGER PRIMARY KEY aliases the rowid, which AUTOINCREMENT keeps from being reused
+CREATE TABLE IF NOT EXISTS sensor_data (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded BIGINT NOT NULL,
+    location VARCHAR NOT NULL,
+    sensor VARCHAR NOT NULL,
+    measurement VARCHAR NOT NULL,
+    units VARCHAR NOT NULL,
+    value DOUBLE PRECISION NOT NULL
+);
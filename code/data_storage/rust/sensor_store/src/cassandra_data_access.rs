@@ -0,0 +1,426 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::config::CassandraConfig;
+use crate::migrations::{cql_statements, now, AppliedMigration, Migration, CASSANDRA_MIGRATIONS, MIGRATIONS_TABLE};
+use crate::retention::RetentionPolicy;
+use crate::sensor_data_access_trait::{batch_outcomes, new_readings, LogOutcome, SensorDataAccess, SensorReadingStream, STREAM_BUFFER};
+use crate::sensor_query::{decode_cursor, encode_cursor, SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use std::collections::HashSet;
+use crate::storage_error::{StorageError, StorageResult};
+use std::sync::Arc;
+use tokio::sync::{mpsc, OnceCell};
+use tokio::task;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use cdrs_tokio::cluster::session::{Session, TcpSessionBuilder, SessionBuilder};
+use cdrs_tokio::cluster::NodeTcpConfigBuilder;
+use cdrs_tokio::load_balancing::RoundRobinLoadBalancingStrategy;
+use cdrs_tokio::transport::TransportTcp;
+use cdrs_tokio::cluster::TcpConnectionManager;
+use cdrs_tokio::query::PreparedQuery;
+use cdrs_tokio::query_values;
+use cdrs_tokio::statement::StatementParamsBuilder;
+use cdrs_tokio::types::rows::Row;
+use cdrs_tokio::types::{CBytes, IntoRustByName};
+use cdrs_tokio::types::value::Value;
+
+// Rows requested per page while streaming
+const CASSANDRA_STREAM_PAGE_SIZE: i32 = 1000;
+// Conditional inserts of one batch in flight at once
+const CASSANDRA_BATCH_CONCURRENCY: usize = 50;
+// Largest TTL the server accepts, 20 years
+const CASSANDRA_MAX_TTL: i64 = 630_720_000;
+
+type CassandraSession = Session<TransportTcp, TcpConnectionManager, RoundRobinLoadBalancingStrategy<TransportTcp, TcpConnectionManager>>;
+
+async fn get_session(config: &CassandraConfig) -> StorageResult<CassandraSession> {
+    let cluster_config = NodeTcpConfigBuilder::new()
+        .with_contact_point(format!("{}:{}", config.host, config.port).into())
+        .build()
+        .await
+        .map_err(|e| StorageError::Connection(format!("Cluster config error: {}", e)))?;
+
+    let lb = RoundRobinLoadBalancingStrategy::new();
+    let session = TcpSessionBuilder::new(lb, cluster_config)
+        .build()
+        .await
+        .map_err(|e| StorageError::Connection(format!("Session build error: {}", e)))?;
+
+    Ok(session)
+}
+
+/// Create the keyspace and its migrations table, which every migration needs before it can run.
+async fn create_keyspace(session: &CassandraSession, config: &CassandraConfig) -> StorageResult<()> {
+    let create_ks = format!(
+        "CREATE KEYSPACE IF NOT EXISTS {} WITH REPLICATION = {{ 'class' : 'SimpleStrategy', 'replication_factor' : 1 }};",
+        config.keyspace
+    );
+    session.query(create_ks).await
+        .map_err(|e| StorageError::Backend(format!("Keyspace create error: {}", e)))?;
+
+    let create_table = format!(
+        "CREATE TABLE IF NOT EXISTS {}.{} (version BIGINT PRIMARY KEY, name TEXT, applied_at BIGINT);",
+        config.keyspace, MIGRATIONS_TABLE
+    );
+    session.query(create_table).await
+        .map_err(|e| StorageError::Backend(format!("Migrations table create error: {}", e)))?;
+    Ok(())
+}
+
+/// Build a SELECT that restricts the partition and clustering range when it can.
+///
+/// `location` is the partition key and `recorded` the first clustering column, so a
+/// `recorded` range is only pushed down together with a location; everything else is
+/// left for `SensorQuery::matches` rather than relying on ALLOW FILTERING.
+fn select_statement(config: &CassandraConfig, query: &SensorQuery) -> (String, Vec<Value>) {
+    let mut select_query = format!(
+        "SELECT location, recorded, sensor, measurement, units, value FROM {}.{}",
+        config.keyspace, config.table
+    );
+    let mut values: Vec<Value> = Vec::new();
+    if let Some(location) = &query.location {
+        select_query.push_str(" WHERE location = ?");
+        values.push(location.clone().into());
+        if let Some(from) = query.recorded_from {
+            select_query.push_str(" AND recorded >= ?");
+            values.push(from.into());
+        }
+        if let Some(to) = query.recorded_to {
+            select_query.push_str(" AND recorded < ?");
+            values.push(to.into());
+        }
+    }
+    select_query.push(';');
+    (select_query, values)
+}
+
+/// A column of the row that must not be null.
+fn column<T>(row: &Row, name: &str) -> StorageResult<T>
+where
+    Row: IntoRustByName<T>,
+{
+    row.get_by_name(name)
+        .map_err(|e| StorageError::Backend(format!("Column {} error: {}", name, e)))?
+        .ok_or_else(|| StorageError::Backend(format!("Column {} is null", name)))
+}
+
+/// Run the query for one page, returning its matching readings and the paging state of the next page.
+async fn fetch_page(session: &CassandraSession, config: &CassandraConfig, query: &SensorQuery, page_size: Option<i32>, paging_state: Option<Vec<u8>>) -> StorageResult<(Vec<SensorReading>, Option<Vec<u8>>)> {
+    let (select_query, values) = select_statement(config, query);
+    // Page with the driver's paging state, which the cursor carries between requests
+    let mut params = StatementParamsBuilder::new().with_values(values.into());
+    if let Some(page_size) = page_size {
+        params = params.with_page_size(page_size);
+    }
+    if let Some(paging_state) = paging_state {
+        params = params.with_paging_state(CBytes::new(paging_state));
+    }
+    let response = session.query_with_params(select_query, params.build()).await
+        .map_err(|e| StorageError::Backend(format!("Query execution error: {}", e)))?;
+    let body = response.response_body()
+        .map_err(|e| StorageError::Backend(format!("Response body error: {}", e)))?;
+    let next = body.as_rows_metadata()
+        .and_then(|metadata| metadata.paging_state.clone())
+        .and_then(CBytes::into_bytes);
+    let rows = body
+        .into_rows()
+        .ok_or_else(|| StorageError::Backend("No rows found".to_string()))?;
+
+    let mut readings: Vec<SensorReading> = Vec::new();
+    for row in rows {
+        let reading = SensorReading {
+            recorded: column(&row, "recorded")?,
+            location: column(&row, "location")?,
+            sensor: column(&row, "sensor")?,
+            measurement: column(&row, "measurement")?,
+            units: column(&row, "units")?,
+            value: column(&row, "value")?,
+        };
+        // Filters that could not be pushed down are applied here
+        if query.matches(&reading) {
+            readings.push(reading);
+        }
+    }
+    Ok((readings, next))
+}
+
+/// Send rows to the stream page by page, stopping if the receiver goes away.
+async fn send_readings(session: &CassandraSession, config: &CassandraConfig, query: &SensorQuery, tx: &mpsc::Sender<StorageResult<SensorReading>>) -> StorageResult<()> {
+    let mut paging_state = None;
+    loop {
+        let (readings, next) = fetch_page(session, config, query, Some(CASSANDRA_STREAM_PAGE_SIZE), paging_state).await?;
+        for reading in readings {
+            if tx.send(Ok(reading)).await.is_err() {
+                return Ok(());
+            }
+        }
+        match next {
+            Some(next) => paging_state = Some(next),
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Cassandra storage sharing one session and its node connections.
+pub struct CassandraDataAccess {
+    session: Arc<CassandraSession>,
+    config: Arc<CassandraConfig>,
+    retention: RetentionPolicy,
+    // Prepared on first use rather than in `connect`, since the table may not exist until migrations run
+    insert_if_new: OnceCell<PreparedQuery>,
+    delete: OnceCell<PreparedQuery>,
+    find_migrations_table: OnceCell<PreparedQuery>,
+}
+
+impl CassandraDataAccess {
+    pub async fn connect(config: &CassandraConfig, retention: &RetentionPolicy) -> StorageResult<Self> {
+        Ok(CassandraDataAccess {
+            session: Arc::new(get_session(config).await?),
+            config: Arc::new(config.clone()),
+            retention: retention.clone(),
+            insert_if_new: OnceCell::new(),
+            delete: OnceCell::new(),
+            find_migrations_table: OnceCell::new(),
+        })
+    }
+
+    /// The statement prepared once per session and reused by every later call.
+    async fn prepared<'a>(&self, cell: &'a OnceCell<PreparedQuery>, query: impl FnOnce() -> String) -> StorageResult<&'a PreparedQuery> {
+        cell.get_or_try_init(|| async move { self.session.prepare(query()).await })
+            .await
+            .map_err(|e| StorageError::Backend(format!("Prepared statement error: {}", e)))
+    }
+
+    /// Insert the reading unless its key is taken, returning the server's `[applied]` flag.
+    async fn insert_if_new(&self, reading: &SensorReading) -> StorageResult<bool> {
+        // A plain INSERT would silently overwrite the row with the same primary key;
+        // IF NOT EXISTS keeps the first one and reports whether it applied
+        let prepared = self.prepared(&self.insert_if_new, || format!(
+            "INSERT INTO {}.{} (location, recorded, sensor, measurement, units, value) VALUES (?, ?, ?, ?, ?, ?) IF NOT EXISTS USING TTL ?;",
+            self.config.keyspace, self.config.table
+        )).await?;
+        let values = query_values!(
+            reading.location.clone(),
+            reading.recorded,
+            reading.sensor.clone(),
+            reading.measurement.clone(),
+            reading.units.clone(),
+            reading.value,
+            self.ttl(reading)
+        );
+        self.session.exec_with_values(prepared, values).await
+            .map_err(|e| StorageError::Backend(format!("Query execution error: {}", e)))?
+            .response_body()
+            .map_err(|e| StorageError::Backend(format!("Response body error: {}", e)))?
+            .into_rows()
+            .and_then(|rows| rows.first().and_then(|row| row.get_by_name("[applied]").ok().flatten()))
+            .ok_or_else(|| StorageError::Backend("Missing [applied] column".to_string()))
+    }
+
+    /// Delete the row stored under the key, whether or not there is one.
+    async fn delete_row(&self, key: &ReadingKey) -> StorageResult<()> {
+        let prepared = self.prepared(&self.delete, || format!(
+            "DELETE FROM {}.{} WHERE location = ? AND recorded = ? AND sensor = ?;",
+            self.config.keyspace, self.config.table
+        )).await?;
+        let values = query_values!(key.location.clone(), key.recorded, key.sensor.clone());
+        self.session.exec_with_values(prepared, values).await
+            .map_err(|e| StorageError::Backend(format!("Delete execution error: {}", e)))?;
+        Ok(())
+    }
+
+    /// Seconds left in the reading's retention window for `USING TTL`, where 0 keeps it forever.
+    fn ttl(&self, reading: &SensorReading) -> i32 {
+        match self.retention.expires_at(reading) {
+            None => 0,
+            // A reading uploaded after its window still needs a positive TTL, so it expires at once
+            Some(expires_at) => (expires_at - now()).clamp(1, CASSANDRA_MAX_TTL) as i32,
+        }
+    }
+}
+
+impl SensorDataAccess for CassandraDataAccess {
+    async fn log_sensor_data(&self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        let applied = self.insert_if_new(reading).await?;
+        if !applied {
+            println!("Duplicate sensor data ignored by Cassandra: {}", reading.to_json());
+            return Ok(LogOutcome::Duplicate);
+        }
+
+        println!("Logging sensor data to Cassandra: {}", reading.to_json());
+        Ok(LogOutcome::Created)
+    }
+
+    async fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        println!("Logging {} sensor readings to Cassandra", readings.len());
+
+        // A conditional batch must stay within one partition and applies all or none of its rows,
+        // so each reading gets its own IF NOT EXISTS insert, several in flight at once. Only the first
+        // copy of a key is sent, and `[applied]` tells whether it or a concurrent write got there first
+        let first = new_readings(readings, &HashSet::new());
+        let inserts: Vec<_> = first.iter().map(|reading| self.insert_if_new(reading)).collect();
+        let applied: Vec<bool> = stream::iter(inserts)
+            .buffered(CASSANDRA_BATCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        let created = first.iter()
+            .zip(applied)
+            .filter(|(_, applied)| *applied)
+            .map(|(reading, _)| reading.key())
+            .collect();
+        Ok(batch_outcomes(readings, created))
+    }
+
+    async fn fetch_sensor_data(&self, query: &SensorQuery) -> StorageResult<SensorPage> {
+        println!("Fetching sensor data from Cassandra: {:?}", query);
+
+        let paging_state = match &query.cursor {
+            None => None,
+            Some(cursor) => Some(decode_cursor(cursor).map_err(StorageError::Validation)?),
+        };
+        let page_size = query.limit.map(|limit| limit as i32);
+        let (readings, paging_state) = fetch_page(&self.session, &self.config, query, page_size, paging_state).await?;
+        let next = paging_state.map(|paging_state| encode_cursor(&paging_state));
+
+        Ok(SensorPage { readings, next })
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        let query = query.clone();
+        let session = self.session.clone();
+        let config = self.config.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        task::spawn(async move {
+            println!("Streaming sensor data from Cassandra: {:?}", query);
+
+            if let Err(e) = send_readings(&session, &config, &query, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+        rx
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        println!("Purging sensor data from Cassandra");
+
+        let truncate_query = format!("TRUNCATE {}.{};", self.config.keyspace, self.config.table);
+        self.session.query(truncate_query).await
+            .map_err(|e| StorageError::Backend(format!("Truncate error: {}", e)))?;
+
+        println!("Cassandra sensor data purged successfully.");
+        Ok(())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        // Every node has system.local, so this needs no keyspace of ours
+        self.session.query("SELECT release_version FROM system.local;").await
+            .map_err(|e| StorageError::Connection(format!("Ping error: {}", e)))?;
+        Ok(())
+    }
+
+    async fn get_sensor_reading(&self, key: &ReadingKey) -> StorageResult<SensorReading> {
+        println!("Fetching sensor reading from Cassandra: {}", key);
+
+        // The key is the table's primary key, so this reads at most one row of one partition
+        let (readings, _) = fetch_page(&self.session, &self.config, &SensorQuery::for_key(key), None, None).await?;
+        readings.into_iter().next().ok_or_else(|| key.not_found())
+    }
+
+    async fn delete_sensor_reading(&self, key: &ReadingKey) -> StorageResult<()> {
+        // Deletes are blind writes in Cassandra, so look the row up to report a missing key
+        self.get_sensor_reading(key).await?;
+        println!("Deleting sensor reading from Cassandra: {}", key);
+        self.delete_row(key).await
+    }
+
+    // Every write carries a TTL from the retention policy; rows written before it was set or shortened are left to the sweeper
+    fn expires_natively(&self) -> bool {
+        true
+    }
+
+    async fn expire_sensor_data(&self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        println!("Expiring sensor data in Cassandra: {:?}", policy);
+
+        // Rows written with a TTL under the current policy go on their own; this catches rows
+        // written before their window was set or shortened. Only readings older than the
+        // shortest window can have expired, so scan those and delete them one by one
+        let Some(shortest) = policy.shortest_window() else {
+            return Ok(0);
+        };
+        let query = SensorQuery { recorded_to: (now - shortest).checked_add(1), ..SensorQuery::default() };
+        let mut rows = self.stream_sensor_data(&query);
+        let mut removed = 0;
+        while let Some(row) = rows.recv().await {
+            let reading = row?;
+            if policy.is_expired(&reading, now) {
+                self.delete_row(&reading.key()).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        CASSANDRA_MIGRATIONS
+    }
+
+    fn migration_script(&self, migration: &Migration) -> String {
+        migration.script
+            .replace("{keyspace}", &self.config.keyspace)
+            .replace("{table}", &self.config.table)
+    }
+
+    async fn applied_migrations(&self) -> StorageResult<Vec<AppliedMigration>> {
+        // Looked up in the schema tables rather than created, so readiness checks never run DDL
+        let prepared = self.prepared(&self.find_migrations_table, || {
+            "SELECT table_name FROM system_schema.tables WHERE keyspace_name = ? AND table_name = ?;".to_string()
+        }).await?;
+        let values = query_values!(self.config.keyspace.clone(), MIGRATIONS_TABLE.to_string());
+        let tables = self.session.exec_with_values(prepared, values).await
+            .map_err(|e| StorageError::Backend(format!("Find migrations table error: {}", e)))?
+            .response_body()
+            .map_err(|e| StorageError::Backend(format!("Response body error: {}", e)))?
+            .into_rows()
+            .unwrap_or_default();
+        if tables.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let select_query = format!("SELECT version, applied_at FROM {}.{};", self.config.keyspace, MIGRATIONS_TABLE);
+        let rows = self.session.query(select_query).await
+            .map_err(|e| StorageError::Backend(format!("Fetch migrations error: {}", e)))?
+            .response_body()
+            .map_err(|e| StorageError::Backend(format!("Response body error: {}", e)))?
+            .into_rows()
+            .unwrap_or_default();
+        let mut applied: Vec<AppliedMigration> = rows.iter()
+            .map(|row| AppliedMigration {
+                version: row.get_by_name("version").ok().flatten().unwrap_or_default(),
+                applied_at: row.get_by_name("applied_at").ok().flatten().unwrap_or_default(),
+            })
+            .collect();
+        applied.sort_by_key(|migration| migration.version);
+        Ok(applied)
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> StorageResult<()> {
+        create_keyspace(&self.session, &self.config).await?;
+        // CQL has no transactions, so scripts use IF NOT EXISTS to stay safe to re-run after a failure
+        for statement in cql_statements(&self.migration_script(migration)) {
+            self.session.query(statement).await
+                .map_err(|e| StorageError::Backend(format!("Migration {} error: {}", migration.version, e)))?;
+        }
+
+        let insert_query = format!(
+            "INSERT INTO {}.{} (version, name, applied_at) VALUES (?, ?, ?);",
+            self.config.keyspace, MIGRATIONS_TABLE
+        );
+        let prepared = self.session.prepare(&insert_query).await
+            .map_err(|e| StorageError::Backend(format!("Prepared statement error: {}", e)))?;
+        let values = query_values!(migration.version, migration.name.to_string(), now());
+        self.session.exec_with_values(&prepared, values).await
+            .map_err(|e| StorageError::Backend(format!("Record migration error: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+//! Connection and retention settings of each backend, as the apps load them from their configuration.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Storage backends selectable with `data_access`, alone or as a comma-separated list.
+pub const BACKENDS: [&str; 8] = ["redis", "mongo", "cassandra", "mysql", "postgres", "sqlite", "jsonl", "memory"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedisConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MongoConfig {
+    pub uri: String,
+    pub database: String,
+    pub collection: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CassandraConfig {
+    pub host: String,
+    pub port: u16,
+    pub keyspace: String,
+    pub table: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MySqlConfig {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PostgresConfig {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+/// A database file on the gateway itself, such as `sqlite://sensor_data.db`, created if missing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SqliteConfig {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+/// A directory of append-only segment files; a new segment starts once the last reaches `segment_bytes`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsonlConfig {
+    pub dir: String,
+    pub segment_bytes: u64,
+}
+
+/// How long readings are kept, as durations such as `30d`; readings with no window are kept forever.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Window of every measurement not listed in `measurements`.
+    pub default: Option<String>,
+    /// Windows by measurement name, such as `temperature = "30d"`.
+    pub measurements: BTreeMap<String, String>,
+    /// How often backends without native expiry delete expired readings.
+    pub sweep_interval: String,
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        RedisConfig { url: "redis://localhost/".to_string() }
+    }
+}
+
+impl Default for MongoConfig {
+    fn default() -> Self {
+        MongoConfig {
+            uri: "mongodb://localhost:27017".to_string(),
+            database: "sensor_data_db".to_string(),
+            collection: "sensor_data".to_string(),
+        }
+    }
+}
+
+impl Default for CassandraConfig {
+    fn default() -> Self {
+        CassandraConfig {
+            host: "localhost".to_string(),
+            port: 9042,
+            keyspace: "sensor_data_db".to_string(),
+            table: "sensor_data".to_string(),
+        }
+    }
+}
+
+impl Default for MySqlConfig {
+    fn default() -> Self {
+        MySqlConfig {
+            url: "mysql://root:@localhost:3306/sensor_data_db".to_string(),
+            max_connections: 5,
+        }
+    }
+}
+
+impl Default for PostgresConfig {
+    fn default() -> Self {
+        PostgresConfig {
+            url: "postgres://postgres:@localhost:5432/sensor_data_db".to_string(),
+            max_connections: 5,
+        }
+    }
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        SqliteConfig {
+            url: "sqlite://sensor_data.db".to_string(),
+            max_connections: 5,
+        }
+    }
+}
+
+impl Default for JsonlConfig {
+    fn default() -> Self {
+        JsonlConfig {
+            dir: "sensor_data".to_string(),
+            segment_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        RetentionConfig {
+            default: None,
+            measurements: BTreeMap::new(),
+            sweep_interval: "1h".to_string(),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::retention::RetentionPolicy;
+use crate::sensor_data_access_trait::{DynSensorDataAccess, LogOutcome, SensorDataAccess, SensorReadingStream};
+use crate::sensor_query::{SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::sensor_stats::{Bucket, SensorStats};
+use crate::storage_error::{StorageError, StorageResult};
+use futures::future::{join_all, BoxFuture};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Which stores must accept a write for it to succeed when writing to several.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum WritePolicy {
+    /// Every store; a write that fails anywhere is reported as failed.
+    #[default]
+    All,
+    /// Only the primary; failures elsewhere are logged.
+    Primary,
+    /// Any one store; failures are logged unless every store failed.
+    BestEffort,
+}
+
+/// A connected store and the `data_access` name it was listed under.
+pub type NamedStore = (String, Arc<dyn DynSensorDataAccess>);
+
+/// The same error kind, naming the store it came from.
+fn from_store(name: &str, e: StorageError) -> StorageError {
+    match e {
+        StorageError::Connection(message) => StorageError::Connection(format!("{}: {}", name, message)),
+        StorageError::Validation(message) => StorageError::Validation(format!("{}: {}", name, message)),
+        StorageError::NotFound(message) => StorageError::NotFound(format!("{}: {}", name, message)),
+        StorageError::Conflict(message) => StorageError::Conflict(format!("{}: {}", name, message)),
+        StorageError::Timeout(message) => StorageError::Timeout(format!("{}: {}", name, message)),
+        StorageError::Backend(message) => StorageError::Backend(format!("{}: {}", name, message)),
+    }
+}
+
+/// Writes to every listed store at once and serves reads from the first, the primary.
+///
+/// Stores that accepted a write are not rolled back when another fails. Logging is
+/// idempotent, so retrying a failed write brings them back in line.
+pub struct FanoutDataAccess {
+    stores: Vec<NamedStore>,
+    policy: WritePolicy,
+}
+
+impl FanoutDataAccess {
+    /// The store itself when only one is listed, otherwise every store behind one fan-out.
+    pub fn combine(mut stores: Vec<NamedStore>, policy: WritePolicy) -> Arc<dyn DynSensorDataAccess> {
+        assert!(!stores.is_empty(), "At least one store is required");
+        if stores.len() == 1 {
+            return stores.remove(0).1;
+        }
+        Arc::new(FanoutDataAccess { stores, policy })
+    }
+
+    fn primary(&self) -> &dyn DynSensorDataAccess {
+        &*self.stores[0].1
+    }
+
+    /// Run a write on every store concurrently and settle the results by the write policy.
+    /// The value returned is the primary's, or under best effort that of the first store that succeeded.
+    async fn write<'a, T>(&'a self, operation: &str, write: impl Fn(&'a dyn DynSensorDataAccess) -> BoxFuture<'a, StorageResult<T>>) -> StorageResult<T> {
+        let results = join_all(self.stores.iter().map(|(_, store)| write(&**store))).await;
+
+        let mut settled: Option<StorageResult<T>> = None;
+        for ((name, _), result) in self.stores.iter().zip(results) {
+            let result = result.map_err(|e| from_store(name, e));
+            let is_primary = settled.is_none();
+            match (&result, self.policy) {
+                (Err(e), WritePolicy::All) => return Err(e.clone()),
+                (Err(e), _) if !is_primary => eprintln!("Fan-out {} to {} failed: {}", operation, name, e),
+                (Err(e), WritePolicy::BestEffort) => eprintln!("Fan-out {} to {} failed: {}", operation, name, e),
+                _ => {}
+            }
+            settled = match settled {
+                None => Some(result),
+                // Best effort settles on the first success when the primary failed
+                Some(Err(_)) if self.policy == WritePolicy::BestEffort && result.is_ok() => Some(result),
+                settled => settled,
+            };
+        }
+        settled.expect("At least one store is required")
+    }
+}
+
+impl SensorDataAccess for FanoutDataAccess {
+    async fn log_sensor_data(&self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        self.write("log", |store| store.log_sensor_data(reading)).await
+    }
+
+    async fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        self.write("batch log", |store| store.log_sensor_data_batch(readings)).await
+    }
+
+    async fn fetch_sensor_data(&self, query: &SensorQuery) -> StorageResult<SensorPage> {
+        self.primary().fetch_sensor_data(query).await
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        self.primary().stream_sensor_data(query)
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        self.write("purge", |store| store.purge_sensor_data()).await
+    }
+
+    async fn aggregate_sensor_data(&self, query: &SensorQuery, bucket: Bucket) -> StorageResult<Vec<SensorStats>> {
+        self.primary().aggregate_sensor_data(query, bucket).await
+    }
+
+    /// Settled by the write policy, like writes, since those are what a down store would fail.
+    async fn ping(&self) -> StorageResult<()> {
+        self.write("ping", |store| store.ping()).await
+    }
+
+    async fn get_sensor_reading(&self, key: &ReadingKey) -> StorageResult<SensorReading> {
+        self.primary().get_sensor_reading(key).await
+    }
+
+    async fn delete_sensor_reading(&self, key: &ReadingKey) -> StorageResult<()> {
+        // A store that never had the reading is already as the delete wants it,
+        // so only the settled store's answer decides whether it was found
+        let found = self.write("delete", |store| Box::pin(async move {
+            match store.delete_sensor_reading(key).await {
+                Ok(()) => Ok(true),
+                Err(StorageError::NotFound(_)) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })).await?;
+        if found { Ok(()) } else { Err(key.not_found()) }
+    }
+
+    /// Only when every store does, since a sweep is needed as soon as one cannot.
+    fn expires_natively(&self) -> bool {
+        self.stores.iter().all(|(_, store)| store.expires_natively())
+    }
+
+    async fn expire_sensor_data(&self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        self.write("expire", |store| store.expire_sensor_data(policy, now)).await
+    }
+}
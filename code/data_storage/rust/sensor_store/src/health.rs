@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::fanout_data_access::{NamedStore, WritePolicy};
+use crate::migrations::pending_migrations;
+use crate::sensor_data_access_trait::DynSensorDataAccess;
+use crate::storage_error::{StorageError, StorageResult};
+use futures::future::join_all;
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// How long a readiness check waits on each step before reporting the store as down.
+pub const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What a readiness check found for one store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoreHealth {
+    pub name: String,
+    /// Whether the store answered its ping.
+    pub reachable: bool,
+    /// Whether every migration this build knows of has been applied.
+    pub migrated: bool,
+    /// Round trip of the ping in milliseconds, or how long it waited before giving up.
+    pub latency_ms: f64,
+    /// Versions of the migrations still to apply.
+    pub pending_migrations: Vec<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl StoreHealth {
+    pub fn is_ready(&self) -> bool {
+        self.reachable && self.migrated
+    }
+}
+
+/// The answer of `/readyz`: whether the service can take traffic, and why per store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub stores: Vec<StoreHealth>,
+}
+
+/// The stores behind the service, kept apart from the fan-out so each is reported on its own.
+pub struct HealthCheck {
+    stores: Vec<NamedStore>,
+    policy: WritePolicy,
+}
+
+impl HealthCheck {
+    pub fn new(stores: Vec<NamedStore>, policy: WritePolicy) -> Self {
+        HealthCheck { stores, policy }
+    }
+
+    /// Check every store at once. The service is ready when the stores the write policy
+    /// relies on are: all of them, the primary, or any one.
+    pub async fn readiness(&self) -> Readiness {
+        let stores = join_all(self.stores.iter().map(|(name, store)| check_store(name, &**store))).await;
+        let ready = match self.policy {
+            WritePolicy::All => stores.iter().all(StoreHealth::is_ready),
+            WritePolicy::Primary => stores.first().is_some_and(StoreHealth::is_ready),
+            WritePolicy::BestEffort => stores.iter().any(StoreHealth::is_ready),
+        };
+        Readiness { ready, stores }
+    }
+}
+
+async fn within_timeout<T>(step: impl Future<Output = StorageResult<T>>) -> StorageResult<T> {
+    tokio::time::timeout(HEALTH_TIMEOUT, step)
+        .await
+        .unwrap_or_else(|_| Err(StorageError::Timeout(format!("No answer within {} ms", HEALTH_TIMEOUT.as_millis()))))
+}
+
+/// Ping the store, then compare its applied migrations with the ones this build knows of.
+async fn check_store(name: &str, access: &dyn DynSensorDataAccess) -> StoreHealth {
+    let started = Instant::now();
+    let ping = within_timeout(access.ping()).await;
+    let mut health = StoreHealth {
+        name: name.to_string(),
+        reachable: ping.is_ok(),
+        migrated: false,
+        latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+        pending_migrations: Vec::new(),
+        error: ping.err().map(|e| e.to_string()),
+    };
+    if health.reachable {
+        match within_timeout(pending_migrations(access)).await {
+            Ok(pending) => {
+                health.migrated = pending.is_empty();
+                health.pending_migrations = pending.iter().map(|migration| migration.version).collect();
+            }
+            Err(e) => health.error = Some(e.to_string()),
+        }
+    }
+    health
+}
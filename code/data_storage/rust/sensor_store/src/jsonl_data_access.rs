@@ -0,0 +1,517 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::config::JsonlConfig;
+use crate::retention::RetentionPolicy;
+use crate::sensor_data_access_trait::{LogOutcome, SensorDataAccess, SensorReadingStream, STREAM_BUFFER};
+use crate::sensor_query::{decode_keyset, encode_keyset, SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::storage_error::{StorageError, StorageResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::mpsc;
+use tokio::task;
+
+// Segment files are named by zero-padded id, so they list in the order they were written
+const SEGMENT_EXTENSION: &str = "jsonl";
+
+/// Where a line starts: its segment id and byte offset. Ordering by it is ordering by write time.
+type Position = (u64, u64);
+
+/// One line of a segment: a reading, or a tombstone for the reading stored under a key.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Record {
+    Deleted { deleted: ReadingKey },
+    Reading(SensorReading),
+}
+
+fn storage_error(context: &str, e: std::io::Error) -> StorageError {
+    StorageError::Backend(format!("JSONL {} error: {}", context, e))
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:08}.{}", id, SEGMENT_EXTENSION))
+}
+
+/// What the time index knows about one segment, enough to skip it for ranges it cannot match.
+#[derive(Debug, Clone, Default)]
+struct Segment {
+    id: u64,
+    bytes: u64,
+    min_recorded: Option<i64>,
+    max_recorded: Option<i64>,
+    /// Newest `recorded` per measurement, so retention can tell when the whole segment has expired.
+    newest: BTreeMap<String, i64>,
+}
+
+impl Segment {
+    fn new(id: u64) -> Self {
+        Segment { id, ..Segment::default() }
+    }
+
+    fn add(&mut self, reading: &SensorReading) {
+        self.min_recorded = Some(self.min_recorded.map_or(reading.recorded, |min| min.min(reading.recorded)));
+        self.max_recorded = Some(self.max_recorded.map_or(reading.recorded, |max| max.max(reading.recorded)));
+        let newest = self.newest.entry(reading.measurement.clone()).or_insert(reading.recorded);
+        *newest = (*newest).max(reading.recorded);
+    }
+
+    /// Whether any reading in the segment can fall in the query's `recorded` range.
+    fn overlaps(&self, query: &SensorQuery) -> bool {
+        match (self.min_recorded, self.max_recorded) {
+            (Some(min), Some(max)) => {
+                query.recorded_from.is_none_or(|from| max >= from) && query.recorded_to.is_none_or(|to| min < to)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_expired(&self, policy: &RetentionPolicy, now: i64) -> bool {
+        self.newest.iter().all(|(measurement, newest)| {
+            policy.window(measurement).is_some_and(|window| newest.saturating_add(window) <= now)
+        })
+    }
+}
+
+/// Call `f` with every complete line of a segment and its offset, reading one line at a time.
+/// Returns the length of the complete lines, short of the file's when a crash left one half-written.
+fn read_segment(path: &Path, mut f: impl FnMut(u64, Record) -> StorageResult<()>) -> StorageResult<u64> {
+    let file = File::open(path).map_err(|e| storage_error("open", e))?;
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    let mut offset = 0;
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line).map_err(|e| storage_error("read", e))?;
+        if read == 0 || line.last() != Some(&b'\n') {
+            return Ok(offset);
+        }
+        let record = serde_json::from_slice(&line).map_err(|e| {
+            StorageError::Backend(format!("JSONL segment {} is corrupt at byte {}: {}", path.display(), offset, e))
+        })?;
+        f(offset, record)?;
+        offset += read as u64;
+    }
+}
+
+/// Cut off a line left half-written by a crash, so the next append starts on a line of its own.
+fn truncate_segment(path: &Path, complete: u64) -> StorageResult<()> {
+    let file = OpenOptions::new().write(true).open(path).map_err(|e| storage_error("open", e))?;
+    let len = file.metadata().map_err(|e| storage_error("stat", e))?.len();
+    if complete < len {
+        println!("Truncating {} incomplete bytes from {}", len - complete, path.display());
+        file.set_len(complete).map_err(|e| storage_error("truncate", e))?;
+        file.sync_data().map_err(|e| storage_error("sync", e))?;
+    }
+    Ok(())
+}
+
+/// Make the directory's removals durable, so each reaches the disk before the next is made.
+fn sync_dir(dir: &Path) -> StorageResult<()> {
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| storage_error("sync directory", e))
+}
+
+/// Segments whose `recorded` ranges overlap, with their lengths when the run was planned.
+/// Every reading in a run was recorded before every reading in the runs after it,
+/// so a run is sorted on its own and the runs are sent one after another.
+#[derive(Debug, Clone)]
+struct Run {
+    segments: Vec<(u64, u64)>,
+    max_recorded: i64,
+}
+
+/// The segments on disk, the key index and the segment being appended to.
+struct JsonlState {
+    dir: PathBuf,
+    segment_bytes: u64,
+    /// Every segment in id order; the last one is active.
+    segments: Vec<Segment>,
+    /// Where the live reading of each key starts.
+    keys: HashMap<ReadingKey, Position>,
+    active: File,
+}
+
+impl JsonlState {
+    /// Replay every segment in order to rebuild the indexes.
+    fn open(config: &JsonlConfig) -> StorageResult<Self> {
+        let dir = PathBuf::from(&config.dir);
+        fs::create_dir_all(&dir).map_err(|e| storage_error("create directory", e))?;
+
+        let mut ids: Vec<u64> = fs::read_dir(&dir)
+            .map_err(|e| storage_error("list directory", e))?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != SEGMENT_EXTENSION {
+                    return None;
+                }
+                path.file_stem()?.to_str()?.parse::<u64>().ok()
+            })
+            .collect();
+        ids.sort_unstable();
+
+        let mut segments = Vec::new();
+        let mut keys = HashMap::new();
+        for id in ids {
+            let mut segment = Segment::new(id);
+            let path = segment_path(&dir, id);
+            let complete = read_segment(&path, |offset, record| {
+                match record {
+                    Record::Reading(reading) => {
+                        segment.add(&reading);
+                        keys.entry(reading.key()).or_insert((id, offset));
+                    }
+                    Record::Deleted { deleted } => {
+                        keys.remove(&deleted);
+                    }
+                }
+                Ok(())
+            })?;
+            truncate_segment(&path, complete)?;
+            segment.bytes = complete;
+            segments.push(segment);
+        }
+        if segments.is_empty() {
+            segments.push(Segment::new(1));
+        }
+
+        let active = open_segment(&dir, segments[segments.len() - 1].id)?;
+        Ok(JsonlState { dir, segment_bytes: config.segment_bytes, segments, keys, active })
+    }
+
+    fn active_segment(&mut self) -> &mut Segment {
+        let last = self.segments.len() - 1;
+        &mut self.segments[last]
+    }
+
+    /// Start a new segment once the active one has reached its size limit.
+    fn rotate_if_full(&mut self) -> StorageResult<()> {
+        if self.active_segment().bytes < self.segment_bytes {
+            return Ok(());
+        }
+        let id = self.active_segment().id + 1;
+        self.active = open_segment(&self.dir, id)?;
+        self.segments.push(Segment::new(id));
+        Ok(())
+    }
+
+    /// Append one line to the active segment, returning where it starts; the caller syncs.
+    fn append(&mut self, record: &Record) -> StorageResult<Position> {
+        self.rotate_if_full()?;
+        let mut line = serde_json::to_vec(record).expect("Record is always serializable");
+        line.push(b'\n');
+        self.active.write_all(&line).map_err(|e| storage_error("write", e))?;
+        let segment = self.active_segment();
+        let position = (segment.id, segment.bytes);
+        segment.bytes += line.len() as u64;
+        Ok(position)
+    }
+
+    fn sync(&self) -> StorageResult<()> {
+        self.active.sync_data().map_err(|e| storage_error("sync", e))
+    }
+
+    fn log(&mut self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        let key = reading.key();
+        if self.keys.contains_key(&key) {
+            return Ok(LogOutcome::Duplicate);
+        }
+        let position = self.append(&Record::Reading(reading.clone()))?;
+        self.active_segment().add(reading);
+        self.keys.insert(key, position);
+        Ok(LogOutcome::Created)
+    }
+
+    fn read_at(&self, (id, offset): Position) -> StorageResult<SensorReading> {
+        let mut file = File::open(segment_path(&self.dir, id)).map_err(|e| storage_error("open", e))?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| storage_error("seek", e))?;
+        let mut line = String::new();
+        BufReader::new(file).read_line(&mut line).map_err(|e| storage_error("read", e))?;
+        match serde_json::from_str(&line) {
+            Ok(Record::Reading(reading)) => Ok(reading),
+            _ => Err(StorageError::Backend(format!("JSONL index points at no reading in segment {} at byte {}", id, offset))),
+        }
+    }
+
+    /// The segments the time index says can match the query, grouped into runs in time order.
+    fn runs(&self, query: &SensorQuery) -> Vec<Run> {
+        let mut segments: Vec<&Segment> = self.segments.iter().filter(|segment| segment.overlaps(query)).collect();
+        segments.sort_by_key(|segment| (segment.min_recorded, segment.id));
+
+        let mut runs: Vec<Run> = Vec::new();
+        for segment in segments {
+            let (Some(min), Some(max)) = (segment.min_recorded, segment.max_recorded) else {
+                continue;
+            };
+            match runs.last_mut() {
+                Some(run) if min <= run.max_recorded => {
+                    run.segments.push((segment.id, segment.bytes));
+                    run.max_recorded = run.max_recorded.max(max);
+                }
+                _ => runs.push(Run { segments: vec![(segment.id, segment.bytes)], max_recorded: max }),
+            }
+        }
+        runs
+    }
+
+    /// Live readings of one run matching the query's filters, ordered by `recorded` and then
+    /// write order. Lines appended since the run was planned are left for a later query.
+    fn scan_run(&self, run: &Run, query: &SensorQuery) -> StorageResult<Vec<(Position, SensorReading)>> {
+        let mut matches = Vec::new();
+        for &(id, bytes) in &run.segments {
+            // A segment dropped by a purge or retention since the run was planned holds nothing live
+            if !self.segments.iter().any(|segment| segment.id == id) {
+                continue;
+            }
+            read_segment(&segment_path(&self.dir, id), |offset, record| {
+                let Record::Reading(reading) = record else {
+                    return Ok(());
+                };
+                let position = (id, offset);
+                // A reading is live while the key index still points at it
+                if offset < bytes && query.matches(&reading) && self.keys.get(&reading.key()) == Some(&position) {
+                    matches.push((position, reading));
+                }
+                Ok(())
+            })?;
+        }
+        matches.sort_by_key(|(position, reading)| (reading.recorded, *position));
+        Ok(matches)
+    }
+
+    /// Matching readings after the cursor's `recorded` value and position, in order, reading
+    /// runs only until the page and one more reading are found, so callers can tell if another follows.
+    fn page(&self, query: &SensorQuery, after: Option<(i64, Position)>) -> StorageResult<Vec<(Position, SensorReading)>> {
+        let mut rows = Vec::new();
+        for run in self.runs(query) {
+            if after.is_some_and(|(recorded, _)| run.max_recorded < recorded) {
+                continue;
+            }
+            rows.extend(
+                self.scan_run(&run, query)?
+                    .into_iter()
+                    .filter(|(position, reading)| after.is_none_or(|after| (reading.recorded, *position) > after)),
+            );
+            if query.limit.is_some_and(|limit| rows.len() > limit) {
+                break;
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Delete the oldest segments, up to the first that is not wholly expired; the active one is kept.
+    ///
+    /// Only a prefix is dropped, so any tombstone dropped with it targets a reading dropped with it too.
+    fn expire(&mut self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        let expired = self.segments[..self.segments.len() - 1]
+            .iter()
+            .take_while(|segment| segment.is_expired(policy, now))
+            .count();
+        self.drop_segments(expired)
+    }
+
+    /// Delete the first `count` segments, oldest first, returning how many live readings went with them.
+    ///
+    /// A tombstone is always written after the reading it deletes, so removing the files oldest first
+    /// and syncing the directory after each means a crash part way leaves a shorter prefix dropped,
+    /// never a reading whose tombstone is gone. The indexes follow only the files actually removed.
+    fn drop_segments(&mut self, count: usize) -> StorageResult<u64> {
+        let mut removed: usize = 0;
+        let result = self.segments[..count].iter().try_for_each(|segment| {
+            match fs::remove_file(segment_path(&self.dir, segment.id)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(storage_error("remove", e)),
+                _ => {}
+            }
+            sync_dir(&self.dir)?;
+            removed += 1;
+            Ok(())
+        });
+
+        let before = self.keys.len();
+        if let Some(last) = removed.checked_sub(1).map(|i| self.segments[i].id) {
+            self.segments.drain(..removed);
+            self.keys.retain(|_, (id, _)| *id > last);
+        }
+        result.map(|_| (before - self.keys.len()) as u64)
+    }
+}
+
+fn open_segment(dir: &Path, id: u64) -> StorageResult<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(dir, id))
+        .map_err(|e| storage_error("open", e))
+}
+
+/// Durable storage in append-only JSON Lines segment files, with no server to run.
+///
+/// Keys and a per-segment time index are kept in memory and rebuilt from the files on startup.
+/// Deletes append tombstones; purge and retention drop whole segments.
+pub struct JsonlDataAccess {
+    state: Arc<Mutex<JsonlState>>,
+}
+
+impl JsonlDataAccess {
+    pub async fn connect(config: &JsonlConfig) -> StorageResult<Self> {
+        let config = config.clone();
+        let state = task::spawn_blocking(move || JsonlState::open(&config))
+            .await
+            .map_err(|e| StorageError::Backend(format!("JSONL open error: {}", e)))??;
+        Ok(JsonlDataAccess { state: Arc::new(Mutex::new(state)) })
+    }
+
+    /// Run file IO off the async runtime, holding the state lock throughout.
+    async fn with_state<T: Send + 'static>(&self, f: impl FnOnce(&mut JsonlState) -> StorageResult<T> + Send + 'static) -> StorageResult<T> {
+        let state = self.state.clone();
+        task::spawn_blocking(move || f(&mut state.lock().unwrap_or_else(PoisonError::into_inner)))
+            .await
+            .map_err(|e| StorageError::Backend(format!("JSONL task error: {}", e)))?
+    }
+}
+
+impl SensorDataAccess for JsonlDataAccess {
+    async fn log_sensor_data(&self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        let owned = reading.clone();
+        let outcome = self.with_state(move |state| {
+            let outcome = state.log(&owned)?;
+            state.sync()?;
+            Ok(outcome)
+        }).await?;
+        match outcome {
+            LogOutcome::Created => println!("Logging sensor data to JSONL: {}", reading.to_json()),
+            LogOutcome::Duplicate => println!("Duplicate sensor data ignored by JSONL: {}", reading.to_json()),
+        }
+        Ok(outcome)
+    }
+
+    async fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        println!("Logging {} sensor readings to JSONL", readings.len());
+        let readings = readings.to_vec();
+        // One sync for the whole batch
+        self.with_state(move |state| {
+            let outcomes = readings.iter().map(|reading| state.log(reading)).collect::<StorageResult<Vec<_>>>()?;
+            state.sync()?;
+            Ok(outcomes)
+        }).await
+    }
+
+    async fn fetch_sensor_data(&self, query: &SensorQuery) -> StorageResult<SensorPage> {
+        println!("Fetching sensor data from JSONL: {:?}", query);
+
+        // The cursor is the `recorded` value and position of the last reading of the previous page
+        let after = match &query.cursor {
+            None => None,
+            Some(cursor) => Some(decode_keyset(cursor)
+                .ok()
+                .and_then(|(recorded, position)| {
+                    let (id, offset) = position.split_once('.')?;
+                    Some((recorded, (id.parse::<u64>().ok()?, offset.parse::<u64>().ok()?)))
+                })
+                .ok_or_else(|| StorageError::Validation("Invalid cursor".to_string()))?),
+        };
+        let page_query = query.clone();
+        let mut rows = self.with_state(move |state| state.page(&page_query, after)).await?;
+
+        let mut next = None;
+        if let Some(limit) = query.limit {
+            if rows.len() > limit {
+                rows.truncate(limit);
+                next = rows.last()
+                    .map(|((id, offset), reading)| encode_keyset(reading.recorded, &format!("{}.{}", id, offset)));
+            }
+        }
+        Ok(SensorPage { readings: rows.into_iter().map(|(_, reading)| reading).collect(), next })
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        let query = query.clone();
+        let state = self.state.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        // Readings are appended in arrival order, so each run of overlapping segments is sorted before
+        // it is sent; the lock is held for one run at a time, so writes carry on while the stream is read
+        task::spawn_blocking(move || {
+            println!("Streaming sensor data from JSONL: {:?}", query);
+
+            let lock = || state.lock().unwrap_or_else(PoisonError::into_inner);
+            let runs = lock().runs(&query);
+            for run in runs {
+                let rows = lock().scan_run(&run, &query);
+                match rows {
+                    Ok(rows) => {
+                        for (_, reading) in rows {
+                            if tx.blocking_send(Ok(reading)).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.blocking_send(Err(e));
+                        return;
+                    }
+                }
+            }
+        });
+        rx
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        println!("Purging sensor data from JSONL");
+
+        self.with_state(|state| {
+            // Start a fresh segment first, so the active one can be dropped with the rest
+            let id = state.active_segment().id + 1;
+            state.active = open_segment(&state.dir, id)?;
+            state.segments.push(Segment::new(id));
+            state.drop_segments(state.segments.len() - 1)?;
+            Ok(())
+        }).await?;
+
+        println!("JSONL sensor data purged successfully.");
+        Ok(())
+    }
+
+    async fn expire_sensor_data(&self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        println!("Expiring sensor data in JSONL: {:?}", policy);
+        let policy = policy.clone();
+        self.with_state(move |state| state.expire(&policy, now)).await
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        // Nothing to connect to, so check the segments' directory is still there
+        self.with_state(|state| match fs::metadata(&state.dir) {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => Err(StorageError::Connection(format!("JSONL directory {} is not a directory", state.dir.display()))),
+            Err(e) => Err(StorageError::Connection(format!("JSONL directory {} error: {}", state.dir.display(), e))),
+        }).await
+    }
+
+    async fn get_sensor_reading(&self, key: &ReadingKey) -> StorageResult<SensorReading> {
+        println!("Fetching sensor reading from JSONL: {}", key);
+        let key = key.clone();
+        self.with_state(move |state| match state.keys.get(&key) {
+            Some(position) => state.read_at(*position),
+            None => Err(key.not_found()),
+        }).await
+    }
+
+    async fn delete_sensor_reading(&self, key: &ReadingKey) -> StorageResult<()> {
+        println!("Deleting sensor reading from JSONL: {}", key);
+        let key = key.clone();
+        self.with_state(move |state| {
+            if !state.keys.contains_key(&key) {
+                return Err(key.not_found());
+            }
+            state.append(&Record::Deleted { deleted: key.clone() })?;
+            state.sync()?;
+            state.keys.remove(&key);
+            Ok(())
+        }).await
+    }
+}
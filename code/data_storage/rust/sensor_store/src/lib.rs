@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+//! Sensor reading storage shared by the legos apps and `rust_ai_data`: the `SensorDataAccess`
+//! trait, a backend for each store, and the migrations, retention, copy and verify tasks built on it.
+
+pub mod cassandra_data_access;
+pub mod config;
+pub mod fanout_data_access;
+pub mod health;
+pub mod jsonl_data_access;
+pub mod memory_data_access;
+pub mod migrations;
+pub mod mongo_data_access;
+pub mod mysql_data_access;
+pub mod postgres_data_access;
+pub mod redis_data_access;
+pub mod report_writer;
+pub mod retention;
+pub mod sensor_data_access_trait;
+pub mod sensor_data_arrow;
+pub mod sensor_data_batch;
+pub mod sensor_data_copy;
+pub mod sensor_data_csv;
+pub mod sensor_data_json_helper;
+pub mod sensor_data_verify;
+pub mod sensor_query;
+pub mod sensor_reading;
+pub mod sensor_stats;
+pub mod sqlite_data_access;
+pub mod storage_error;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::retention::RetentionPolicy;
+use crate::sensor_data_access_trait::{LogOutcome, SensorDataAccess, SensorReadingStream, STREAM_BUFFER};
+use crate::sensor_query::{decode_keyset, encode_keyset, SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::storage_error::{StorageError, StorageResult};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tokio::sync::mpsc;
+use tokio::task;
+
+// Readings ordered by `recorded`, then by the order they were logged, like the SQL
+// backends' `ORDER BY recorded, id`; the sequence number plays the part of `id`.
+type Position = (i64, u64);
+
+#[derive(Default)]
+struct MemoryState {
+    readings: BTreeMap<Position, SensorReading>,
+    keys: HashMap<ReadingKey, Position>,
+    next_id: u64,
+}
+
+impl MemoryState {
+    fn log(&mut self, reading: &SensorReading) -> LogOutcome {
+        let key = reading.key();
+        if self.keys.contains_key(&key) {
+            return LogOutcome::Duplicate;
+        }
+        let position = (reading.recorded, self.next_id);
+        self.next_id += 1;
+        self.keys.insert(key, position);
+        self.readings.insert(position, reading.clone());
+        LogOutcome::Created
+    }
+
+    /// Readings at or after `start` in order, as far as the query's `recorded` range allows.
+    fn range<'a>(&'a self, query: &'a SensorQuery, start: Bound<Position>) -> impl Iterator<Item = (&'a Position, &'a SensorReading)> {
+        let start = match (start, query.recorded_from) {
+            (Bound::Unbounded, Some(from)) => Bound::Included((from, 0)),
+            (start, _) => start,
+        };
+        // Stop at `to` by hand, since `range` panics on a start past its end
+        let to = query.recorded_to;
+        self.readings.range((start, Bound::Unbounded))
+            .take_while(move |((recorded, _), _)| to.is_none_or(|to| *recorded < to))
+            .filter(|(_, reading)| query.matches(reading))
+    }
+
+    fn remove(&mut self, position: &Position) {
+        if let Some(reading) = self.readings.remove(position) {
+            self.keys.remove(&reading.key());
+        }
+    }
+}
+
+/// Storage held in process memory, for tests and demos; everything is lost when the service stops.
+#[derive(Default)]
+pub struct MemoryDataAccess {
+    state: Mutex<MemoryState>,
+}
+
+impl MemoryDataAccess {
+    pub fn new() -> Self {
+        MemoryDataAccess::default()
+    }
+
+    // No lock is held across an await, so a poisoned lock only means a panic elsewhere
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl SensorDataAccess for MemoryDataAccess {
+    async fn log_sensor_data(&self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        let outcome = self.state().log(reading);
+        match outcome {
+            LogOutcome::Created => println!("Logging sensor data to memory: {}", reading.to_json()),
+            LogOutcome::Duplicate => println!("Duplicate sensor data ignored by memory: {}", reading.to_json()),
+        }
+        Ok(outcome)
+    }
+
+    async fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        println!("Logging {} sensor readings to memory", readings.len());
+        let mut state = self.state();
+        Ok(readings.iter().map(|reading| state.log(reading)).collect())
+    }
+
+    async fn fetch_sensor_data(&self, query: &SensorQuery) -> StorageResult<SensorPage> {
+        println!("Fetching sensor data from memory: {:?}", query);
+        let state = self.state();
+
+        let Some(limit) = query.limit else {
+            let readings = state.range(query, Bound::Unbounded).map(|(_, reading)| reading.clone()).collect();
+            return Ok(SensorPage { readings, next: None });
+        };
+        // The cursor is the position of the last reading of the previous page
+        let start = match &query.cursor {
+            None => Bound::Unbounded,
+            Some(cursor) => decode_keyset(cursor)
+                .ok()
+                .and_then(|(recorded, id)| Some(Bound::Excluded((recorded, id.parse::<u64>().ok()?))))
+                .ok_or_else(|| StorageError::Validation("Invalid cursor".to_string()))?,
+        };
+
+        // Take one extra reading to learn whether another page follows
+        let mut page: Vec<(&Position, &SensorReading)> = state.range(query, start).take(limit + 1).collect();
+        let mut next = None;
+        if page.len() > limit {
+            page.truncate(limit);
+            next = page.last().map(|((recorded, id), _)| encode_keyset(*recorded, &id.to_string()));
+        }
+        let readings = page.into_iter().map(|(_, reading)| reading.clone()).collect();
+        Ok(SensorPage { readings, next })
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        println!("Streaming sensor data from memory: {:?}", query);
+        // Snapshot the matches so the lock is not held while a slow consumer drains the channel
+        let readings: Vec<SensorReading> = self.state()
+            .range(query, Bound::Unbounded)
+            .map(|(_, reading)| reading.clone())
+            .collect();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        task::spawn(async move {
+            for reading in readings {
+                if tx.send(Ok(reading)).await.is_err() {
+                    return;
+                }
+            }
+        });
+        rx
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        println!("Purging sensor data from memory");
+        let mut state = self.state();
+        state.readings.clear();
+        state.keys.clear();
+        Ok(())
+    }
+
+    async fn expire_sensor_data(&self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        println!("Expiring sensor data in memory: {:?}", policy);
+        let mut state = self.state();
+        let expired: Vec<Position> = state.readings
+            .iter()
+            .filter(|(_, reading)| policy.is_expired(reading, now))
+            .map(|(position, _)| *position)
+            .collect();
+        for position in &expired {
+            state.remove(position);
+        }
+        Ok(expired.len() as u64)
+    }
+
+    // Always reachable, being in the same process
+    async fn ping(&self) -> StorageResult<()> {
+        Ok(())
+    }
+
+    async fn get_sensor_reading(&self, key: &ReadingKey) -> StorageResult<SensorReading> {
+        println!("Fetching sensor reading from memory: {}", key);
+        let state = self.state();
+        state.keys.get(key)
+            .and_then(|position| state.readings.get(position))
+            .cloned()
+            .ok_or_else(|| key.not_found())
+    }
+
+    async fn delete_sensor_reading(&self, key: &ReadingKey) -> StorageResult<()> {
+        println!("Deleting sensor reading from memory: {}", key);
+        let mut state = self.state();
+        let position = state.keys.get(key).copied().ok_or_else(|| key.not_found())?;
+        state.remove(&position);
+        Ok(())
+    }
+}
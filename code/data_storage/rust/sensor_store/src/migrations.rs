@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_data_access_trait::DynSensorDataAccess;
+use crate::storage_error::StorageResult;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One versioned schema change, embedded from `migrations/<backend>/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub script: &'static str,
+}
+
+/// A migration applied to the backend, as recorded in its migrations table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub applied_at: i64,
+}
+
+pub const MYSQL_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "create_sensor_data", script: include_str!("../migrations/mysql/0001_create_sensor_data.sql") },
+    Migration { version: 2, name: "index_recorded", script: include_str!("../migrations/mysql/0002_index_recorded.sql") },
+    Migration { version: 3, name: "unique_reading_key", script: include_str!("../migrations/mysql/0003_unique_reading_key.sql") },
+];
+
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "create_sensor_data", script: include_str!("../migrations/postgres/0001_create_sensor_data.sql") },
+    Migration { version: 2, name: "index_recorded", script: include_str!("../migrations/postgres/0002_index_recorded.sql") },
+    Migration { version: 3, name: "unique_reading_key", script: include_str!("../migrations/postgres/0003_unique_reading_key.sql") },
+];
+
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "create_sensor_data", script: include_str!("../migrations/sqlite/0001_create_sensor_data.sql") },
+    Migration { version: 2, name: "index_recorded", script: include_str!("../migrations/sqlite/0002_index_recorded.sql") },
+    Migration { version: 3, name: "unique_reading_key", script: include_str!("../migrations/sqlite/0003_unique_reading_key.sql") },
+];
+
+pub const CASSANDRA_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "create_sensor_data", script: include_str!("../migrations/cassandra/0001_create_sensor_data.cql") },
+];
+
+pub const MONGO_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "index_recorded", script: include_str!("../migrations/mongo/0001_index_recorded.json") },
+    Migration { version: 2, name: "unique_reading_key", script: include_str!("../migrations/mongo/0002_unique_reading_key.json") },
+    Migration { version: 3, name: "expire_at_ttl", script: include_str!("../migrations/mongo/0003_expire_at_ttl.json") },
+];
+
+pub const REDIS_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "sorted_set_from_list", script: include_str!("../migrations/redis/0001_sorted_set_from_list.lua") },
+];
+
+/// Name of the table, collection or hash each backend records applied migrations in.
+pub const MIGRATIONS_TABLE: &str = "schema_migrations";
+
+/// Seconds since the epoch, for `applied_at` and retention cutoffs.
+pub fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Split a CQL script into statements, dropping `--` comment lines, since the driver runs one at a time.
+pub fn cql_statements(script: &str) -> Vec<String> {
+    let code: String = script
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    code.split(';')
+        .map(str::trim)
+        .filter(|statement| !statement.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Migrations the backend has not recorded yet, oldest first.
+pub async fn pending_migrations(access: &dyn DynSensorDataAccess) -> StorageResult<Vec<Migration>> {
+    let applied = access.applied_migrations().await?;
+    Ok(access
+        .migrations()
+        .iter()
+        .filter(|migration| !applied.iter().any(|a| a.version == migration.version))
+        .copied()
+        .collect())
+}
+
+/// Apply every pending migration in order, stopping at the first failure.
+pub async fn migrate(access: &dyn DynSensorDataAccess) -> StorageResult<Vec<Migration>> {
+    let pending = pending_migrations(access).await?;
+    for migration in &pending {
+        access.apply_migration(migration).await?;
+        println!("Applied migration {:04} {}", migration.version, migration.name);
+    }
+    Ok(pending)
+}
+
+/// Print each known migration with when it was applied, or `pending`.
+pub async fn print_status(access: &dyn DynSensorDataAccess) -> StorageResult<()> {
+    let applied = access.applied_migrations().await?;
+    for migration in access.migrations() {
+        match applied.iter().find(|a| a.version == migration.version) {
+            Some(a) => println!("{:04} {:<24} applied {}", migration.version, migration.name, a.applied_at),
+            None => println!("{:04} {:<24} pending", migration.version, migration.name),
+        }
+    }
+    // Versions recorded by a newer build that this one does not know about
+    for a in applied.iter().filter(|a| !access.migrations().iter().any(|m| m.version == a.version)) {
+        println!("{:04} {:<24} applied {}", a.version, "(unknown)", a.applied_at);
+    }
+    Ok(())
+}
+
+/// Print the scripts `migrate` would run, without running them.
+pub async fn print_dry_run(access: &dyn DynSensorDataAccess) -> StorageResult<()> {
+    let pending = pending_migrations(access).await?;
+    if pending.is_empty() {
+        println!("No pending migrations");
+    }
+    for migration in pending {
+        println!("-- Would apply {:04} {}", migration.version, migration.name);
+        println!("{}", access.migration_script(&migration).trim_end());
+    }
+    Ok(())
+}
+
+/// The `migrate` subcommand: list the status, show a dry run or apply what is pending.
+pub async fn run_migrate(access: &dyn DynSensorDataAccess, status: bool, dry_run: bool) -> StorageResult<()> {
+    if status {
+        print_status(access).await
+    } else if dry_run {
+        print_dry_run(access).await
+    } else {
+        migrate(access).await.map(|_| ())
+    }
+}
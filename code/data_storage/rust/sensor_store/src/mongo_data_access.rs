@@ -0,0 +1,477 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::config::MongoConfig;
+use crate::migrations::{now, AppliedMigration, Migration, MIGRATIONS_TABLE, MONGO_MIGRATIONS};
+use crate::retention::RetentionPolicy;
+use crate::sensor_data_access_trait::{LogOutcome, SensorDataAccess, SensorReadingStream, STREAM_BUFFER};
+use crate::sensor_query::{decode_keyset, encode_keyset, SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::sensor_stats::{Bucket, SensorStats};
+use crate::storage_error::{StorageError, StorageResult};
+use tokio::sync::mpsc;
+use tokio::task;
+use mongodb::{options::{AggregateOptions, ClientOptions, FindOneOptions, FindOptions, InsertManyOptions}, Client, Collection, Database, bson::doc, bson::oid::ObjectId, bson::Bson, bson::DateTime, bson::Document};
+use mongodb::error::{BulkWriteFailure, ErrorKind, WriteFailure};
+use futures::stream::StreamExt;
+
+// Server code for a write that violates a unique index
+const DUPLICATE_KEY: i32 = 11000;
+
+/// Classify a driver error so callers can tell unreachable servers from rejected writes.
+fn storage_error(context: &str, e: mongodb::error::Error) -> StorageError {
+    let message = format!("{} error: {}", context, e);
+    match e.kind.as_ref() {
+        ErrorKind::Io(_) | ErrorKind::DnsResolve { .. } | ErrorKind::ConnectionPoolCleared { .. } => StorageError::Connection(message),
+        ErrorKind::ServerSelection { .. } => StorageError::Timeout(message),
+        ErrorKind::InvalidArgument { .. } => StorageError::Validation(message),
+        ErrorKind::Write(WriteFailure::WriteError(write)) if write.code == DUPLICATE_KEY => StorageError::Conflict(message),
+        _ => StorageError::Backend(message),
+    }
+}
+
+/// Translate the query into a Mongo filter document.
+fn query_filter(query: &SensorQuery) -> Document {
+    let mut filter = doc! {};
+    let mut recorded = doc! {};
+    if let Some(from) = query.recorded_from {
+        recorded.insert("$gte", from);
+    }
+    if let Some(to) = query.recorded_to {
+        recorded.insert("$lt", to);
+    }
+    if !recorded.is_empty() {
+        filter.insert("recorded", recorded);
+    }
+    if let Some(location) = &query.location {
+        filter.insert("location", location);
+    }
+    if let Some(sensor) = &query.sensor {
+        filter.insert("sensor", sensor);
+    }
+    if let Some(measurement) = &query.measurement {
+        filter.insert("measurement", measurement);
+    }
+    filter
+}
+
+/// Match the documents stored under a key.
+fn key_filter(key: &ReadingKey) -> Document {
+    doc! { "location": &key.location, "sensor": &key.sensor, "recorded": key.recorded }
+}
+
+/// The stored document; `expire_at` is set when the reading has a retention window, for the TTL index.
+fn reading_to_doc(reading: &SensorReading, retention: &RetentionPolicy) -> Document {
+    let mut doc = doc! {
+        "recorded": reading.recorded,
+        "location": &reading.location,
+        "sensor": &reading.sensor,
+        "measurement": &reading.measurement,
+        "units": &reading.units,
+        "value": reading.value
+    };
+    if let Some(expires_at) = retention.expires_at(reading) {
+        doc.insert("expire_at", DateTime::from_millis(expires_at.saturating_mul(1000)));
+    }
+    doc
+}
+
+/// A field every stored document must hold; a missing or mistyped one is an error rather than a zero.
+fn field<'a, T>(doc: &'a Document, name: &str, read: impl FnOnce(&'a Bson) -> Option<T>) -> StorageResult<T> {
+    let value = doc.get(name)
+        .ok_or_else(|| StorageError::Backend(format!("Field {} is missing", name)))?;
+    read(value).ok_or_else(|| StorageError::Backend(format!("Field {} has unexpected type {:?}", name, value.element_type())))
+}
+
+// Integers written by other clients may come back as 32-bit
+fn as_integer(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int64(n) => Some(*n),
+        Bson::Int32(n) => Some(i64::from(*n)),
+        _ => None,
+    }
+}
+
+fn as_number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Double(n) => Some(*n),
+        _ => as_integer(value).map(|n| n as f64),
+    }
+}
+
+fn as_string(value: &Bson) -> Option<String> {
+    value.as_str().map(str::to_string)
+}
+
+fn reading_from_doc(doc: &Document) -> StorageResult<SensorReading> {
+    Ok(SensorReading {
+        recorded: field(doc, "recorded", as_integer)?,
+        location: field(doc, "location", as_string)?,
+        sensor: field(doc, "sensor", as_string)?,
+        measurement: field(doc, "measurement", as_string)?,
+        units: field(doc, "units", as_string)?,
+        value: field(doc, "value", as_number)?,
+    })
+}
+
+/// Read one `$group` result of `aggregate_sensor_data`, whose `_id` holds the bucket and series.
+fn stats_from_doc(doc: &Document) -> StorageResult<SensorStats> {
+    let id = field(doc, "_id", Bson::as_document)?;
+    Ok(SensorStats {
+        bucket_start: field(id, "bucket_start", as_integer)?,
+        location: field(id, "location", as_string)?,
+        sensor: field(id, "sensor", as_string)?,
+        measurement: field(id, "measurement", as_string)?,
+        units: field(id, "units", as_string)?,
+        count: field(doc, "count", as_integer)?,
+        min: field(doc, "min", as_number)?,
+        max: field(doc, "max", as_number)?,
+        avg: field(doc, "avg", as_number)?,
+    })
+}
+
+/// Send documents to the stream as the cursor yields them, stopping if the receiver goes away.
+async fn send_readings(collection: &Collection<Document>, query: &SensorQuery, tx: &mpsc::Sender<StorageResult<SensorReading>>) -> StorageResult<()> {
+    let options = FindOptions::builder().sort(doc! { "recorded": 1, "_id": 1 }).build();
+    let mut cursor = collection.find(query_filter(query), options).await
+        .map_err(|e| storage_error("Find", e))?;
+    while let Some(result) = cursor.next().await {
+        let doc = result
+            .map_err(|e| storage_error("Cursor", e))?;
+        if tx.send(Ok(reading_from_doc(&doc)?)).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// MongoDB storage sharing one client and its connection pool.
+pub struct MongoDataAccess {
+    database: Database,
+    collection: Collection<Document>,
+    retention: RetentionPolicy,
+}
+
+impl MongoDataAccess {
+    pub async fn connect(config: &MongoConfig, retention: &RetentionPolicy) -> StorageResult<Self> {
+        let options = ClientOptions::parse(&config.uri).await
+            .map_err(|e| storage_error("ClientOptions", e))?;
+        let client = Client::with_options(options)
+            .map_err(|e| storage_error("Client", e))?;
+        let database = client.database(&config.database);
+        Ok(MongoDataAccess {
+            collection: database.collection(&config.collection),
+            database,
+            retention: retention.clone(),
+        })
+    }
+
+    /// Delete all but the first document, by `_id`, of each group sharing the index key,
+    /// so a unique index can be built over documents logged before it was enforced.
+    async fn drop_duplicates(&self, key: &Document) -> StorageResult<u64> {
+        let group_id: Document = key.keys().map(|field| (field.clone(), Bson::String(format!("${}", field)))).collect();
+        let pipeline = vec![
+            doc! { "$sort": { "_id": 1 } },
+            doc! { "$group": { "_id": group_id, "ids": { "$push": "$_id" } } },
+            doc! { "$match": { "ids.1": { "$exists": true } } },
+        ];
+        let options = AggregateOptions::builder().allow_disk_use(true).build();
+        let mut cursor = self.collection.aggregate(pipeline, options).await
+            .map_err(|e| storage_error("Aggregate", e))?;
+
+        let mut removed = 0;
+        while let Some(result) = cursor.next().await {
+            let group = result
+                .map_err(|e| storage_error("Cursor", e))?;
+            let ids = group.get_array("ids")
+                .map_err(|e| StorageError::Backend(format!("Duplicate group error: {}", e)))?;
+            removed += self.collection.delete_many(doc! { "_id": { "$in": ids[1..].to_vec() } }, None).await
+                .map_err(|e| storage_error("Delete", e))?
+                .deleted_count;
+        }
+        Ok(removed)
+    }
+}
+
+impl SensorDataAccess for MongoDataAccess {
+    async fn log_sensor_data(&self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        let bson_doc = reading_to_doc(reading, &self.retention);
+        // The unique index on (location, sensor, recorded) rejects a reading logged twice
+        match self.collection.insert_one(bson_doc, None).await.map_err(|e| storage_error("Insert", e)) {
+            Ok(_) => {}
+            Err(StorageError::Conflict(_)) => {
+                println!("Duplicate sensor data ignored by Mongo: {}", reading.to_json());
+                return Ok(LogOutcome::Duplicate);
+            }
+            Err(e) => return Err(e),
+        }
+
+        println!("Logging sensor data to Mongo: {}", reading.to_json());
+        Ok(LogOutcome::Created)
+    }
+
+    async fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        println!("Logging {} sensor readings to Mongo", readings.len());
+        if readings.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Unordered, so a duplicate does not stop the rest; each one comes back as a write error at its index
+        let options = InsertManyOptions::builder().ordered(false).build();
+        let mut outcomes = vec![LogOutcome::Created; readings.len()];
+        if let Err(e) = self.collection.insert_many(readings.iter().map(|reading| reading_to_doc(reading, &self.retention)), options).await {
+            match e.kind.as_ref() {
+                ErrorKind::BulkWrite(BulkWriteFailure { write_errors: Some(write_errors), write_concern_error: None, .. })
+                    if write_errors.iter().all(|write| write.code == DUPLICATE_KEY) =>
+                {
+                    for write in write_errors {
+                        outcomes[write.index] = LogOutcome::Duplicate;
+                    }
+                }
+                _ => return Err(storage_error("Insert", e)),
+            }
+        }
+        Ok(outcomes)
+    }
+
+    async fn fetch_sensor_data(&self, query: &SensorQuery) -> StorageResult<SensorPage> {
+        println!("Fetching sensor data from Mongo: {:?}", query);
+
+        let mut filter = query_filter(query);
+        // Keyset pagination on (recorded, _id), resuming after the last document of the previous page
+        if let Some(cursor) = &query.cursor {
+            let (recorded, id) = decode_keyset(cursor)
+                .ok()
+                .and_then(|(recorded, id)| ObjectId::parse_str(id).ok().map(|id| (recorded, id)))
+                .ok_or_else(|| StorageError::Validation("Invalid cursor".to_string()))?;
+            filter = doc! { "$and": [filter, { "$or": [
+                { "recorded": { "$gt": recorded } },
+                { "recorded": recorded, "_id": { "$gt": id } },
+            ] }] };
+        }
+        // Fetch one extra document to learn whether another page follows
+        let options = FindOptions::builder()
+            .sort(doc! { "recorded": 1, "_id": 1 })
+            .limit(query.limit.map(|limit| limit as i64 + 1))
+            .build();
+
+        let mut cursor = self.collection.find(filter, options).await
+            .map_err(|e| storage_error("Find", e))?;
+
+        let mut docs: Vec<Document> = Vec::new();
+        while let Some(result) = cursor.next().await {
+            let doc = result
+                .map_err(|e| storage_error("Cursor", e))?;
+            docs.push(doc);
+        }
+
+        let mut next = None;
+        if let Some(limit) = query.limit {
+            if docs.len() > limit {
+                docs.truncate(limit);
+                if let Some(last) = docs.last() {
+                    let id = field(last, "_id", Bson::as_object_id)?;
+                    next = Some(encode_keyset(field(last, "recorded", as_integer)?, &id.to_hex()));
+                }
+            }
+        }
+
+        let readings = docs.iter().map(reading_from_doc).collect::<StorageResult<_>>()?;
+
+        Ok(SensorPage { readings, next })
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        let query = query.clone();
+        let collection = self.collection.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        task::spawn(async move {
+            println!("Streaming sensor data from Mongo: {:?}", query);
+
+            if let Err(e) = send_readings(&collection, &query, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+        rx
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        println!("Purging sensor data from Mongo");
+
+        self.collection.delete_many(doc! {}, None).await
+            .map_err(|e| storage_error("Delete", e))?;
+
+        println!("Mongo sensor data purged successfully.");
+        Ok(())
+    }
+
+    async fn aggregate_sensor_data(&self, query: &SensorQuery, bucket: Bucket) -> StorageResult<Vec<SensorStats>> {
+        println!("Aggregating sensor data in Mongo per {}s: {:?}", bucket.seconds(), query);
+
+        let pipeline = vec![
+            doc! { "$match": query_filter(query) },
+            doc! { "$group": {
+                "_id": {
+                    "bucket_start": { "$subtract": ["$recorded", { "$mod": ["$recorded", bucket.seconds()] }] },
+                    "location": "$location",
+                    "sensor": "$sensor",
+                    "measurement": "$measurement",
+                    "units": "$units",
+                },
+                "count": { "$sum": 1_i64 },
+                "min": { "$min": "$value" },
+                "max": { "$max": "$value" },
+                "avg": { "$avg": "$value" },
+            } },
+            doc! { "$sort": { "_id.bucket_start": 1, "_id.location": 1, "_id.sensor": 1, "_id.measurement": 1, "_id.units": 1 } },
+        ];
+        let mut cursor = self.collection.aggregate(pipeline, None).await
+            .map_err(|e| storage_error("Aggregate", e))?;
+
+        let mut stats = Vec::new();
+        while let Some(result) = cursor.next().await {
+            let doc = result
+                .map_err(|e| storage_error("Cursor", e))?;
+            stats.push(stats_from_doc(&doc)?);
+        }
+        Ok(stats)
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        self.database.run_command(doc! { "ping": 1 }, None).await
+            .map_err(|e| storage_error("ping", e))?;
+        Ok(())
+    }
+
+    async fn get_sensor_reading(&self, key: &ReadingKey) -> StorageResult<SensorReading> {
+        println!("Fetching sensor reading from Mongo: {}", key);
+
+        let options = FindOneOptions::builder().sort(doc! { "_id": 1 }).build();
+        let doc = self.collection.find_one(key_filter(key), options).await
+            .map_err(|e| storage_error("Find", e))?;
+        match doc {
+            Some(doc) => reading_from_doc(&doc),
+            None => Err(key.not_found()),
+        }
+    }
+
+    async fn delete_sensor_reading(&self, key: &ReadingKey) -> StorageResult<()> {
+        println!("Deleting sensor reading from Mongo: {}", key);
+
+        let result = self.collection.delete_many(key_filter(key), None).await
+            .map_err(|e| storage_error("Delete", e))?;
+        if result.deleted_count == 0 {
+            return Err(key.not_found());
+        }
+        Ok(())
+    }
+
+    // The TTL index on `expire_at` removes readings written with a retention window;
+    // documents written before the window was set or shortened are left to the sweeper
+    fn expires_natively(&self) -> bool {
+        true
+    }
+
+    async fn expire_sensor_data(&self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        println!("Expiring sensor data in Mongo: {:?}", policy);
+
+        let mut removed = 0;
+        for (measurement, window) in &policy.measurements {
+            removed += self.collection.delete_many(doc! { "measurement": measurement, "recorded": { "$lte": now - window } }, None).await
+                .map_err(|e| storage_error("Expire", e))?
+                .deleted_count;
+        }
+        if let Some(window) = policy.default {
+            // Measurements with their own window were swept above
+            let measurements: Vec<&String> = policy.measurements.keys().collect();
+            removed += self.collection.delete_many(doc! { "measurement": { "$nin": measurements }, "recorded": { "$lte": now - window } }, None).await
+                .map_err(|e| storage_error("Expire", e))?
+                .deleted_count;
+        }
+        Ok(removed)
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        MONGO_MIGRATIONS
+    }
+
+    fn migration_script(&self, migration: &Migration) -> String {
+        migration.script.replace("{collection}", self.collection.name())
+    }
+
+    async fn applied_migrations(&self) -> StorageResult<Vec<AppliedMigration>> {
+        let options = FindOptions::builder().sort(doc! { "version": 1 }).build();
+        let mut cursor = self.database.collection::<Document>(MIGRATIONS_TABLE).find(doc! {}, options).await
+            .map_err(|e| storage_error("Fetch migrations", e))?;
+        let mut applied = Vec::new();
+        while let Some(result) = cursor.next().await {
+            let doc = result.map_err(|e| storage_error("Cursor", e))?;
+            applied.push(AppliedMigration {
+                version: field(&doc, "version", as_integer)?,
+                applied_at: field(&doc, "applied_at", as_integer)?,
+            });
+        }
+        Ok(applied)
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> StorageResult<()> {
+        // Each script is one database command, such as createIndexes
+        let command: Document = serde_json::from_str(&self.migration_script(migration))
+            .map_err(|e| StorageError::Backend(format!("Migration {} parse error: {}", migration.version, e)))?;
+
+        // Keep the first copy of each reading logged before a unique index enforced its key
+        let indexes = command.get_array("indexes").map(Vec::as_slice).unwrap_or_default();
+        for index in indexes.iter().filter_map(Bson::as_document) {
+            if index.get_bool("unique").unwrap_or(false) {
+                if let Ok(key) = index.get_document("key") {
+                    let removed = self.drop_duplicates(key).await?;
+                    println!("Removed {} duplicate readings from Mongo before migration {}", removed, migration.version);
+                }
+            }
+        }
+        self.database.run_command(command, None).await
+            .map_err(|e| storage_error(&format!("Migration {}", migration.version), e))?;
+
+        let record = doc! { "version": migration.version, "name": migration.name, "applied_at": now() };
+        self.database.collection::<Document>(MIGRATIONS_TABLE).insert_one(record, None).await
+            .map_err(|e| storage_error("Record migration", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored() -> Document {
+        doc! { "recorded": 1_756_000_000_i64, "location": "den", "sensor": "bmp280", "measurement": "temperature", "units": "C", "value": 21.5 }
+    }
+
+    #[test]
+    fn documents_read_back_as_readings() {
+        let reading = reading_from_doc(&stored()).unwrap();
+        assert_eq!(reading.recorded, 1_756_000_000);
+        assert_eq!(reading.value, 21.5);
+
+        // 32-bit integers, as other clients may write them, are still numbers
+        let mut doc = stored();
+        doc.insert("recorded", 1_756_000_000_i32);
+        doc.insert("value", 21_i32);
+        assert_eq!(reading_from_doc(&doc).unwrap().value, 21.0);
+    }
+
+    #[test]
+    fn missing_or_mistyped_fields_are_errors() {
+        let mut missing = stored();
+        missing.remove("units");
+        assert!(matches!(reading_from_doc(&missing), Err(StorageError::Backend(message)) if message.contains("units")));
+
+        let mut mistyped = stored();
+        mistyped.insert("value", "banana");
+        assert!(matches!(reading_from_doc(&mistyped), Err(StorageError::Backend(message)) if message.contains("value")));
+
+        let mut null = stored();
+        null.insert("recorded", Bson::Null);
+        assert!(reading_from_doc(&null).is_err());
+    }
+}
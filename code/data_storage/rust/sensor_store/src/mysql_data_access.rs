@@ -0,0 +1,416 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::config::MySqlConfig;
+use crate::migrations::{now, AppliedMigration, Migration, MYSQL_MIGRATIONS, MIGRATIONS_TABLE};
+use crate::retention::RetentionPolicy;
+use crate::sensor_data_access_trait::{batch_outcomes, LogOutcome, SensorDataAccess, SensorReadingStream, BATCH_CHUNK, STREAM_BUFFER};
+use crate::sensor_query::{decode_keyset, encode_keyset, SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::sensor_stats::{Bucket, SensorStats};
+use crate::storage_error::{StorageError, StorageResult};
+use std::collections::HashSet;
+use tokio::sync::mpsc;
+use tokio::task;
+use futures::stream::TryStreamExt;
+use sqlx::mysql::{MySqlConnection, MySqlPoolOptions, MySqlRow};
+use sqlx::{Acquire, Executor, MySql, MySqlPool, QueryBuilder, Row};
+
+/// Classify a driver error so callers can tell unreachable servers from rejected statements.
+fn storage_error(context: &str, e: sqlx::Error) -> StorageError {
+    let message = format!("{} error: {}", context, e);
+    match &e {
+        sqlx::Error::PoolTimedOut => StorageError::Timeout(message),
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolClosed => StorageError::Connection(message),
+        sqlx::Error::RowNotFound => StorageError::NotFound(message),
+        sqlx::Error::Database(db) if db.is_unique_violation() => StorageError::Conflict(message),
+        _ => StorageError::Backend(message),
+    }
+}
+
+/// Start a SELECT of every reading column with a `WHERE` term for each filter set on the query.
+fn select_readings(query: &SensorQuery) -> QueryBuilder<'static, MySql> {
+    filtered_select(
+        "SELECT id, recorded, location, sensor, measurement, units, CAST(value AS DOUBLE) as value FROM sensor_data",
+        query,
+    )
+}
+
+/// Start `select` with a `WHERE` term for each filter set on the query.
+fn filtered_select(select: &str, query: &SensorQuery) -> QueryBuilder<'static, MySql> {
+    let mut builder = QueryBuilder::<MySql>::new(select);
+    builder.push(" WHERE 1 = 1");
+    if let Some(from) = query.recorded_from {
+        builder.push(" AND recorded >= ").push_bind(from);
+    }
+    if let Some(to) = query.recorded_to {
+        builder.push(" AND recorded < ").push_bind(to);
+    }
+    if let Some(location) = &query.location {
+        builder.push(" AND location = ").push_bind(location.clone());
+    }
+    if let Some(sensor) = &query.sensor {
+        builder.push(" AND sensor = ").push_bind(sensor.clone());
+    }
+    if let Some(measurement) = &query.measurement {
+        builder.push(" AND measurement = ").push_bind(measurement.clone());
+    }
+    builder
+}
+
+fn reading_from_row(row: &MySqlRow) -> SensorReading {
+    SensorReading {
+        recorded: row.get("recorded"),
+        location: row.get("location"),
+        sensor: row.get("sensor"),
+        measurement: row.get("measurement"),
+        units: row.get("units"),
+        value: row.get("value"),
+    }
+}
+
+fn stats_from_row(row: &MySqlRow) -> SensorStats {
+    SensorStats {
+        bucket_start: row.get("bucket_start"),
+        location: row.get("location"),
+        sensor: row.get("sensor"),
+        measurement: row.get("measurement"),
+        units: row.get("units"),
+        count: row.get("count"),
+        min: row.get("min"),
+        max: row.get("max"),
+        avg: row.get("avg"),
+    }
+}
+
+fn key_from_row(row: &MySqlRow) -> ReadingKey {
+    ReadingKey {
+        location: row.get("location"),
+        sensor: row.get("sensor"),
+        recorded: row.get("recorded"),
+    }
+}
+
+/// Keys of the readings that the connection's transaction can see.
+async fn visible_keys(con: &mut MySqlConnection, readings: &[SensorReading]) -> StorageResult<HashSet<ReadingKey>> {
+    let mut select = QueryBuilder::<MySql>::new(
+        "SELECT location, sensor, recorded FROM sensor_data WHERE (location, sensor, recorded) IN ",
+    );
+    select.push_tuples(readings, |mut row, reading| {
+        row.push_bind(&reading.location)
+            .push_bind(&reading.sensor)
+            .push_bind(reading.recorded);
+    });
+    let rows = select.build()
+        .fetch_all(con)
+        .await
+        .map_err(|e| storage_error("Fetch", e))?;
+    Ok(rows.iter().map(key_from_row).collect())
+}
+
+/// A multi-row INSERT of the readings that leaves a row already stored under a key as it is.
+fn insert_readings(readings: &[SensorReading]) -> QueryBuilder<'_, MySql> {
+    let mut builder = QueryBuilder::<MySql>::new(
+        "INSERT INTO sensor_data (recorded, location, sensor, measurement, units, value) ",
+    );
+    builder.push_values(readings, |mut row, reading| {
+        row.push_bind(reading.recorded)
+            .push_bind(&reading.location)
+            .push_bind(&reading.sensor)
+            .push_bind(&reading.measurement)
+            .push_bind(&reading.units)
+            .push_bind(reading.value);
+    });
+    builder.push(" ON DUPLICATE KEY UPDATE id = id");
+    builder
+}
+
+/// Send rows to the stream as the driver yields them, stopping if the receiver goes away.
+async fn send_readings(pool: &MySqlPool, query: &SensorQuery, tx: &mpsc::Sender<StorageResult<SensorReading>>) -> StorageResult<()> {
+    let mut builder = select_readings(query);
+    builder.push(" ORDER BY recorded, id");
+    let mut rows = builder.build().fetch(pool);
+    while let Some(row) = rows.try_next().await.map_err(|e| storage_error("Fetch", e))? {
+        if tx.send(Ok(reading_from_row(&row))).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// MySQL storage sharing one connection pool.
+pub struct MySQLDataAccess {
+    pool: MySqlPool,
+}
+
+impl MySQLDataAccess {
+    pub async fn connect(config: &MySqlConfig) -> StorageResult<Self> {
+        let pool = MySqlPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.url)
+            .await
+            .map_err(|e| storage_error("Pool", e))?;
+        Ok(MySQLDataAccess { pool })
+    }
+}
+
+impl SensorDataAccess for MySQLDataAccess {
+    async fn log_sensor_data(&self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        let query = "INSERT INTO sensor_data (recorded, location, sensor, measurement, units, value) VALUES (?, ?, ?, ?, ?, ?)";
+        let result = sqlx::query(query)
+            .bind(reading.recorded)
+            .bind(&reading.location)
+            .bind(&reading.sensor)
+            .bind(&reading.measurement)
+            .bind(&reading.units)
+            .bind(reading.value)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| storage_error("Insert", e));
+        // The unique key on (location, sensor, recorded) rejects a reading logged twice;
+        // INSERT IGNORE would also hide unrelated errors
+        match result {
+            Ok(_) => {}
+            Err(StorageError::Conflict(_)) => {
+                println!("Duplicate sensor data ignored by MySQL: {}", reading.to_json());
+                return Ok(LogOutcome::Duplicate);
+            }
+            Err(e) => return Err(e),
+        }
+
+        println!("Logging sensor data to MySQL: {}", reading.to_json());
+        Ok(LogOutcome::Created)
+    }
+
+    async fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        println!("Logging {} sensor readings to MySQL", readings.len());
+
+        // Chunks commit on their own; retrying after a failure reports the committed ones as duplicates
+        let mut outcomes = Vec::with_capacity(readings.len());
+        for chunk in readings.chunks(BATCH_CHUNK) {
+            let mut con = self.pool.acquire().await.map_err(|e| storage_error("Acquire", e))?;
+            // Both lookups below must read the same snapshot, whatever the server's default isolation
+            con.execute("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ").await
+                .map_err(|e| storage_error("Isolation", e))?;
+            let mut tx = con.begin().await.map_err(|e| storage_error("Begin", e))?;
+
+            // MySQL has no RETURNING, so the keys the snapshot sees before and after the insert tell
+            // which readings this chunk stored. A key another writer commits meanwhile makes the insert
+            // leave that row alone and stays out of the snapshot, so it is reported as a duplicate.
+            // Unlike INSERT IGNORE, the upsert still fails on truncation and other errors
+            let stored = visible_keys(&mut tx, chunk).await?;
+            insert_readings(chunk)
+                .build()
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| storage_error("Insert", e))?;
+            let created = visible_keys(&mut tx, chunk).await?
+                .into_iter()
+                .filter(|key| !stored.contains(key))
+                .collect();
+            tx.commit().await.map_err(|e| storage_error("Commit", e))?;
+            outcomes.extend(batch_outcomes(chunk, created));
+        }
+        Ok(outcomes)
+    }
+
+    async fn fetch_sensor_data(&self, query: &SensorQuery) -> StorageResult<SensorPage> {
+        println!("Fetching sensor data from MySQL: {:?}", query);
+
+        let mut builder = select_readings(query);
+        // Keyset pagination on (recorded, id), resuming after the last row of the previous page
+        if let Some(cursor) = &query.cursor {
+            let (recorded, id) = decode_keyset(cursor)
+                .ok()
+                .and_then(|(recorded, id)| id.parse::<i64>().ok().map(|id| (recorded, id)))
+                .ok_or_else(|| StorageError::Validation("Invalid cursor".to_string()))?;
+            builder.push(" AND (recorded, id) > (").push_bind(recorded).push(", ").push_bind(id).push(")");
+        }
+        builder.push(" ORDER BY recorded, id");
+        // Fetch one extra row to learn whether another page follows
+        if let Some(limit) = query.limit {
+            builder.push(" LIMIT ").push_bind(limit as i64 + 1);
+        }
+        let mut rows = builder.build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| storage_error("Fetch", e))?;
+
+        let mut next = None;
+        if let Some(limit) = query.limit {
+            if rows.len() > limit {
+                rows.truncate(limit);
+                next = rows.last()
+                    .map(|last| encode_keyset(last.get("recorded"), &last.get::<i64, _>("id").to_string()));
+            }
+        }
+
+        let readings = rows.iter().map(reading_from_row).collect();
+
+        Ok(SensorPage { readings, next })
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        let query = query.clone();
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        task::spawn(async move {
+            println!("Streaming sensor data from MySQL: {:?}", query);
+
+            if let Err(e) = send_readings(&pool, &query, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+        rx
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        println!("Purging sensor data from MySQL");
+
+        sqlx::query("DELETE FROM sensor_data")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| storage_error("Delete", e))?;
+
+        println!("MySQL sensor data purged successfully.");
+        Ok(())
+    }
+
+    async fn expire_sensor_data(&self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        println!("Expiring sensor data in MySQL: {:?}", policy);
+
+        let mut removed = 0;
+        for (measurement, window) in &policy.measurements {
+            removed += sqlx::query("DELETE FROM sensor_data WHERE measurement = ? AND recorded <= ?")
+                .bind(measurement)
+                .bind(now - window)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| storage_error("Expire", e))?
+                .rows_affected();
+        }
+        if let Some(window) = policy.default {
+            let mut builder = QueryBuilder::<MySql>::new("DELETE FROM sensor_data WHERE recorded <= ");
+            builder.push_bind(now - window);
+            // Measurements with their own window were swept above
+            if !policy.measurements.is_empty() {
+                builder.push(" AND measurement NOT IN (");
+                let mut separated = builder.separated(", ");
+                for measurement in policy.measurements.keys() {
+                    separated.push_bind(measurement);
+                }
+                separated.push_unseparated(")");
+            }
+            removed += builder.build()
+                .execute(&self.pool)
+                .await
+                .map_err(|e| storage_error("Expire", e))?
+                .rows_affected();
+        }
+        Ok(removed)
+    }
+
+    async fn aggregate_sensor_data(&self, query: &SensorQuery, bucket: Bucket) -> StorageResult<Vec<SensorStats>> {
+        println!("Aggregating sensor data in MySQL per {}s: {:?}", bucket.seconds(), query);
+
+        // The width is an integer, so it is inlined rather than bound
+        let select = format!(
+            "SELECT recorded - MOD(recorded, {}) AS bucket_start, location, sensor, measurement, units, COUNT(*) AS count, \
+             CAST(MIN(value) AS DOUBLE) AS min, CAST(MAX(value) AS DOUBLE) AS max, CAST(AVG(value) AS DOUBLE) AS avg FROM sensor_data",
+            bucket.seconds()
+        );
+        let mut builder = filtered_select(&select, query);
+        builder.push(" GROUP BY 1, 2, 3, 4, 5 ORDER BY 1, 2, 3, 4, 5");
+        let rows = builder.build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| storage_error("Aggregate", e))?;
+        Ok(rows.iter().map(stats_from_row).collect())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| storage_error("Ping", e))?;
+        Ok(())
+    }
+
+    async fn get_sensor_reading(&self, key: &ReadingKey) -> StorageResult<SensorReading> {
+        println!("Fetching sensor reading from MySQL: {}", key);
+
+        let mut builder = select_readings(&SensorQuery::for_key(key));
+        builder.push(" ORDER BY id LIMIT 1");
+        let row = builder.build()
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| storage_error("Fetch", e))?;
+        row.as_ref().map(reading_from_row).ok_or_else(|| key.not_found())
+    }
+
+    async fn delete_sensor_reading(&self, key: &ReadingKey) -> StorageResult<()> {
+        println!("Deleting sensor reading from MySQL: {}", key);
+
+        let result = sqlx::query("DELETE FROM sensor_data WHERE location = ? AND sensor = ? AND recorded = ?")
+            .bind(&key.location)
+            .bind(&key.sensor)
+            .bind(key.recorded)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| storage_error("Delete", e))?;
+        if result.rows_affected() == 0 {
+            return Err(key.not_found());
+        }
+        Ok(())
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        MYSQL_MIGRATIONS
+    }
+
+    async fn applied_migrations(&self) -> StorageResult<Vec<AppliedMigration>> {
+        // Looked up in the catalog rather than created, so readiness checks never run DDL
+        let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = ?")
+            .bind(MIGRATIONS_TABLE)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| storage_error("Find migrations table", e))?;
+        if tables == 0 {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(&format!("SELECT version, applied_at FROM {} ORDER BY version", MIGRATIONS_TABLE))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| storage_error("Fetch migrations", e))?;
+        Ok(rows.iter()
+            .map(|row| AppliedMigration { version: row.get("version"), applied_at: row.get("applied_at") })
+            .collect())
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> StorageResult<()> {
+        let create_table_query = format!(
+            "CREATE TABLE IF NOT EXISTS {} (version BIGINT PRIMARY KEY, name VARCHAR(255) NOT NULL, applied_at BIGINT NOT NULL)",
+            MIGRATIONS_TABLE
+        );
+        sqlx::query(&create_table_query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| storage_error("Create migrations table", e))?;
+
+        // MySQL commits DDL implicitly, so only the version record is covered by the transaction
+        let mut tx = self.pool.begin().await.map_err(|e| storage_error("Begin", e))?;
+        // A plain string runs as a simple query, which may hold several statements
+        tx.execute(migration.script)
+            .await
+            .map_err(|e| storage_error(&format!("Migration {}", migration.version), e))?;
+        sqlx::query(&format!("INSERT INTO {} (version, name, applied_at) VALUES (?, ?, ?)", MIGRATIONS_TABLE))
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(now())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| storage_error("Record migration", e))?;
+        tx.commit().await.map_err(|e| storage_error("Commit", e))
+    }
+}
@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::config::RedisConfig;
+use crate::migrations::{now, AppliedMigration, Migration, MIGRATIONS_TABLE, REDIS_MIGRATIONS};
+use crate::retention::RetentionPolicy;
+use crate::sensor_data_access_trait::{LogOutcome, SensorDataAccess, SensorReadingStream, STREAM_BUFFER};
+use crate::sensor_query::{decode_cursor, encode_cursor, SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::storage_error::{StorageError, StorageResult};
+use std::collections::BTreeMap;
+use tokio::sync::mpsc;
+use tokio::task;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+
+// Readings are members of a sorted set scored by `recorded`, so time ranges
+// are served by ZRANGEBYSCORE instead of scanning a list.
+const REDIS_ZSET_KEY: &str = "sensor_data:by_recorded";
+// The list readings were pushed to before the sorted set; the first migration moves it into the set
+const REDIS_LEGACY_LIST_KEY: &str = "sensor_data";
+// Legacy list entries the first migration could not validate, kept as they were for inspection
+const REDIS_QUARANTINE_KEY: &str = "sensor_data:quarantine";
+// Members read per ZRANGEBYSCORE call while streaming
+const REDIS_STREAM_CHUNK: isize = 1000;
+// A member is the whole reading, so a retry with a changed value would be a new member.
+// This adds the reading only when no member at its score has the same location and
+// sensor, atomically, and returns how many members were added.
+const REDIS_LOG_SCRIPT: &str = r#"
+for _, member in ipairs(redis.call('ZRANGEBYSCORE', KEYS[1], ARGV[1], ARGV[1])) do
+    local reading = cjson.decode(member)
+    if reading.location == ARGV[2] and reading.sensor == ARGV[3] then
+        return 0
+    end
+end
+return redis.call('ZADD', KEYS[1], ARGV[1], ARGV[4])
+"#;
+
+/// Classify a driver error so callers can tell unreachable servers from rejected commands.
+fn storage_error(context: &str, e: redis::RedisError) -> StorageError {
+    let message = format!("Redis {} error: {}", context, e);
+    if e.is_timeout() {
+        StorageError::Timeout(message)
+    } else if e.is_io_error() || e.is_connection_refusal() || e.is_connection_dropped() {
+        StorageError::Connection(message)
+    } else {
+        StorageError::Backend(message)
+    }
+}
+
+/// Run `REDIS_LOG_SCRIPT` for one reading.
+fn log_invocation<'a>(script: &'a redis::Script, reading: &SensorReading) -> redis::ScriptInvocation<'a> {
+    let mut invocation = script.key(REDIS_ZSET_KEY);
+    invocation
+        .arg(reading.recorded)
+        .arg(&reading.location)
+        .arg(&reading.sensor)
+        .arg(reading.to_json());
+    invocation
+}
+
+/// Members are written by this service or validated by the first migration, so one that
+/// does not parse means the set is corrupt.
+fn reading_from_member(value: &str) -> StorageResult<SensorReading> {
+    SensorReading::from_json(value).map_err(|e| StorageError::Backend(format!("Redis member error: {}", e)))
+}
+
+/// Members scored at the key's `recorded` value that belong to its location and sensor, with their readings.
+async fn members_for_key(con: &mut ConnectionManager, key: &ReadingKey) -> StorageResult<Vec<(String, SensorReading)>> {
+    let values: Vec<String> = con.zrangebyscore(REDIS_ZSET_KEY, key.recorded, key.recorded).await
+        .map_err(|e| storage_error("ZRANGEBYSCORE", e))?;
+    let mut members = Vec::new();
+    for value in values {
+        let reading = reading_from_member(&value)?;
+        if key.matches(&reading) {
+            members.push((value, reading));
+        }
+    }
+    Ok(members)
+}
+
+/// Send members to the stream one ZRANGEBYSCORE chunk at a time, stopping if the receiver goes away.
+async fn send_readings(mut con: ConnectionManager, query: &SensorQuery, tx: &mpsc::Sender<StorageResult<SensorReading>>) -> StorageResult<()> {
+    let min = query.recorded_from.map_or("-inf".to_string(), |from| from.to_string());
+    let max = query.recorded_to.map_or("+inf".to_string(), |to| format!("({}", to));
+
+    let mut offset: isize = 0;
+    loop {
+        let values: Vec<String> = con.zrangebyscore_limit(REDIS_ZSET_KEY, &min, &max, offset, REDIS_STREAM_CHUNK).await
+            .map_err(|e| storage_error("ZRANGEBYSCORE", e))?;
+        let fetched = values.len() as isize;
+        for value in values {
+            let reading = reading_from_member(&value)?;
+            if query.matches(&reading) && tx.send(Ok(reading)).await.is_err() {
+                return Ok(());
+            }
+        }
+        if fetched < REDIS_STREAM_CHUNK {
+            return Ok(());
+        }
+        offset += fetched;
+    }
+}
+
+/// Redis storage sharing one multiplexed connection, which reconnects on its own after failures.
+pub struct RedisDataAccess {
+    con: ConnectionManager,
+}
+
+impl RedisDataAccess {
+    pub async fn connect(config: &RedisConfig) -> StorageResult<Self> {
+        let client = redis::Client::open(config.url.as_str())
+            .map_err(|e| storage_error("client", e))?;
+        let con = ConnectionManager::new(client)
+            .await
+            .map_err(|e| StorageError::Connection(format!("Redis connection error: {}", e)))?;
+        Ok(RedisDataAccess { con })
+    }
+}
+
+impl SensorDataAccess for RedisDataAccess {
+    async fn log_sensor_data(&self, reading: &SensorReading) -> StorageResult<LogOutcome> {
+        let mut con = self.con.clone();
+        let cleaned_json = reading.to_json();
+
+        let script = redis::Script::new(REDIS_LOG_SCRIPT);
+        let added: i64 = log_invocation(&script, reading)
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| storage_error("EVALSHA", e))?;
+        if added == 0 {
+            println!("Duplicate sensor data ignored by Redis: {}", cleaned_json);
+            return Ok(LogOutcome::Duplicate);
+        }
+
+        println!("Logging sensor data to Redis: {}", cleaned_json);
+        Ok(LogOutcome::Created)
+    }
+
+    async fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> StorageResult<Vec<LogOutcome>> {
+        let mut con = self.con.clone();
+        println!("Logging {} sensor readings to Redis", readings.len());
+        if readings.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // One pipeline of script calls; each runs atomically, in order, so later copies see earlier ones
+        let script = redis::Script::new(REDIS_LOG_SCRIPT);
+        let mut pipe = redis::pipe();
+        pipe.load_script(&script).ignore();
+        for reading in readings {
+            pipe.invoke_script(&log_invocation(&script, reading));
+        }
+        let added: Vec<i64> = pipe.query_async(&mut con).await
+            .map_err(|e| storage_error("EVALSHA", e))?;
+        Ok(added.into_iter()
+            .map(|added| if added == 0 { LogOutcome::Duplicate } else { LogOutcome::Created })
+            .collect())
+    }
+
+    async fn fetch_sensor_data(&self, query: &SensorQuery) -> StorageResult<SensorPage> {
+        let mut con = self.con.clone();
+        println!("Fetching sensor data from Redis: {:?}", query);
+
+        let min = query.recorded_from.map_or("-inf".to_string(), |from| from.to_string());
+        let max = query.recorded_to.map_or("+inf".to_string(), |to| format!("({}", to));
+
+        let mut next = None;
+        let values: Vec<String> = match query.limit {
+            None => con.zrangebyscore(REDIS_ZSET_KEY, min, max).await
+                .map_err(|e| storage_error("ZRANGEBYSCORE", e))?,
+            Some(limit) => {
+                // The cursor is the offset of the next member within the score range
+                let offset = match &query.cursor {
+                    None => 0,
+                    Some(cursor) => decode_cursor(cursor)
+                        .ok()
+                        .and_then(|bytes| String::from_utf8(bytes).ok())
+                        .and_then(|offset| offset.parse::<isize>().ok())
+                        .ok_or_else(|| StorageError::Validation("Invalid cursor".to_string()))?,
+                };
+                // Fetch one extra member to learn whether another page follows
+                let mut values: Vec<String> = con.zrangebyscore_limit(REDIS_ZSET_KEY, min, max, offset, limit as isize + 1).await
+                    .map_err(|e| storage_error("ZRANGEBYSCORE", e))?;
+                if values.len() > limit {
+                    values.truncate(limit);
+                    next = Some(encode_cursor((offset + limit as isize).to_string().as_bytes()));
+                }
+                values
+            }
+        };
+
+        let mut readings: Vec<SensorReading> = Vec::new();
+        for value in values {
+            let reading = reading_from_member(&value)?;
+            // Location, sensor and measurement are not indexed, so filter them here
+            if query.matches(&reading) {
+                readings.push(reading);
+            }
+        }
+
+        Ok(SensorPage { readings, next })
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        let query = query.clone();
+        let con = self.con.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        task::spawn(async move {
+            println!("Streaming sensor data from Redis: {:?}", query);
+
+            if let Err(e) = send_readings(con, &query, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+        rx
+    }
+
+    async fn purge_sensor_data(&self) -> StorageResult<()> {
+        let mut con = self.con.clone();
+        println!("Purging sensor data from Redis");
+
+        con.del::<_, ()>(REDIS_ZSET_KEY).await
+            .map_err(|e| storage_error("DEL", e))?;
+
+        println!("Redis sensor data purged successfully.");
+        Ok(())
+    }
+
+    async fn expire_sensor_data(&self, policy: &RetentionPolicy, now: i64) -> StorageResult<u64> {
+        let mut con = self.con.clone();
+        println!("Expiring sensor data in Redis: {:?}", policy);
+
+        // With one window for every measurement, the expired readings are exactly a score range
+        if policy.measurements.is_empty() {
+            let Some(window) = policy.default else {
+                return Ok(0);
+            };
+            return con.zrembyscore(REDIS_ZSET_KEY, "-inf", now - window).await
+                .map_err(|e| storage_error("ZREMRANGEBYSCORE", e));
+        }
+
+        // Otherwise only members older than the shortest window can have expired, so check those one by one
+        let Some(shortest) = policy.shortest_window() else {
+            return Ok(0);
+        };
+        let max = now - shortest;
+        let mut removed = 0;
+        let mut offset: isize = 0;
+        loop {
+            let values: Vec<String> = con.zrangebyscore_limit(REDIS_ZSET_KEY, "-inf", max, offset, REDIS_STREAM_CHUNK).await
+                .map_err(|e| storage_error("ZRANGEBYSCORE", e))?;
+            let fetched = values.len() as isize;
+            let mut expired = Vec::new();
+            for value in values {
+                if policy.is_expired(&reading_from_member(&value)?, now) {
+                    expired.push(value);
+                }
+            }
+            if !expired.is_empty() {
+                removed += con.zrem::<_, _, u64>(REDIS_ZSET_KEY, &expired).await
+                    .map_err(|e| storage_error("ZREM", e))?;
+            }
+            if fetched < REDIS_STREAM_CHUNK {
+                return Ok(removed);
+            }
+            // Removed members no longer count toward the offset of the next chunk
+            offset += fetched - expired.len() as isize;
+        }
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        let mut con = self.con.clone();
+        redis::cmd("PING").query_async::<String>(&mut con).await
+            .map_err(|e| storage_error("PING", e))?;
+        Ok(())
+    }
+
+    async fn get_sensor_reading(&self, key: &ReadingKey) -> StorageResult<SensorReading> {
+        let mut con = self.con.clone();
+        println!("Fetching sensor reading from Redis: {}", key);
+
+        members_for_key(&mut con, key).await?
+            .into_iter()
+            .next()
+            .map(|(_, reading)| reading)
+            .ok_or_else(|| key.not_found())
+    }
+
+    async fn delete_sensor_reading(&self, key: &ReadingKey) -> StorageResult<()> {
+        let mut con = self.con.clone();
+        println!("Deleting sensor reading from Redis: {}", key);
+
+        let members: Vec<String> = members_for_key(&mut con, key).await?
+            .into_iter()
+            .map(|(member, _)| member)
+            .collect();
+        if members.is_empty() {
+            return Err(key.not_found());
+        }
+        con.zrem::<_, _, ()>(REDIS_ZSET_KEY, members).await
+            .map_err(|e| storage_error("ZREM", e))?;
+        Ok(())
+    }
+    fn migrations(&self) -> &'static [Migration] {
+        REDIS_MIGRATIONS
+    }
+
+    async fn applied_migrations(&self) -> StorageResult<Vec<AppliedMigration>> {
+        let mut con = self.con.clone();
+        // Applied times by version, in version order
+        let recorded: BTreeMap<i64, i64> = con.hgetall(MIGRATIONS_TABLE).await
+            .map_err(|e| storage_error("HGETALL", e))?;
+        Ok(recorded
+            .into_iter()
+            .map(|(version, applied_at)| AppliedMigration { version, applied_at })
+            .collect())
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> StorageResult<()> {
+        let mut con = self.con.clone();
+        // Each script is Lua run atomically over the sorted set, the legacy list and the quarantine list,
+        // returns how many readings it changed and quarantined, and is safe to re-run
+        let script = redis::Script::new(migration.script);
+        let (changed, quarantined): (i64, i64) = script
+            .key(REDIS_ZSET_KEY)
+            .key(REDIS_LEGACY_LIST_KEY)
+            .key(REDIS_QUARANTINE_KEY)
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| storage_error(&format!("Migration {}", migration.version), e))?;
+        println!("Redis migration {} changed {} readings", migration.version, changed);
+        if quarantined > 0 {
+            println!("Redis migration {} moved {} invalid entries to {}", migration.version, quarantined, REDIS_QUARANTINE_KEY);
+        }
+
+        con.hset::<_, _, _, ()>(MIGRATIONS_TABLE, migration.version, now()).await
+            .map_err(|e| storage_error("Record migration", e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_data_access_trait::SensorReadingStream;
+use crate::sensor_data_csv::CSV_HEADER;
+use crate::sensor_reading::SensorReading;
+use crate::storage_error::StorageResult;
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::Value;
+
+pub use report_writer::{negotiate, NegotiationError, ReportFormat, ReportRow, ReportWriter, REPORT_WRITERS};
+
+impl ReportRow for SensorReading {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            self.recorded.into(),
+            self.location.as_str().into(),
+            self.sensor.as_str().into(),
+            self.measurement.as_str().into(),
+            self.units.as_str().into(),
+            self.value.into(),
+        ]
+    }
+    fn to_json(&self) -> String {
+        SensorReading::to_json(self)
+    }
+}
+
+/// A complete document for a page of readings.
+pub fn render_report(writer: &dyn ReportWriter, readings: &[SensorReading]) -> String {
+    report_writer::render_report(writer, &CSV_HEADER, readings)
+}
+
+/// Wait for the first streamed reading, so a backend that fails before sending anything
+/// is reported with an error status instead of an empty report under `200 OK`.
+pub async fn first_reading(rows: &mut SensorReadingStream) -> StorageResult<Option<SensorReading>> {
+    rows.recv().await.transpose()
+}
+
+/// Turn streamed readings, after the `first` already received, into document chunks for a
+/// chunked HTTP body. An error after the headers are sent ends the stream with that error,
+/// which aborts the body before its final chunk so clients cannot take it for a whole report.
+pub fn report_stream(
+    writer: &'static dyn ReportWriter,
+    first: Option<SensorReading>,
+    rows: SensorReadingStream,
+) -> impl Stream<Item = Result<String, Box<dyn std::error::Error + Send + Sync>>> {
+    let begin = stream::once(async move { Ok(writer.begin(&CSV_HEADER)) });
+    let start = usize::from(first.is_some());
+    let first = stream::iter(first.map(|reading| Ok(writer.row(0, &reading))));
+    let rows = stream::unfold((rows, start), move |(mut rows, index)| async move {
+        rows.recv()
+            .await
+            .map(|row| (row.map(|reading| writer.row(index, &reading)).map_err(Into::into), (rows, index + 1)))
+    });
+    let end = stream::once(async move { Ok(writer.end()) });
+    begin.chain(first).chain(rows).chain(end)
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::config::RetentionConfig;
+use crate::migrations::now;
+use crate::sensor_data_access_trait::DynSensorDataAccess;
+use crate::sensor_reading::SensorReading;
+use crate::sensor_stats::parse_duration;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Retention windows in seconds; a reading expires once its window has passed since `recorded`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RetentionPolicy {
+    pub default: Option<i64>,
+    pub measurements: BTreeMap<String, i64>,
+    pub sweep_interval: i64,
+}
+
+/// How the configured backend applies the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Enforcement {
+    /// No window is configured, so every reading is kept.
+    None,
+    /// The backend expires readings itself with TTLs fixed as they are written, and the sweeper
+    /// also runs for readings written before their window was set or shortened. A reading whose
+    /// window is lengthened later still goes when its original TTL runs out.
+    NativeAndSweeper,
+    /// A background task deletes expired readings every `sweep_interval`.
+    Sweeper,
+}
+
+impl Enforcement {
+    /// Whether `run_sweeper` should run.
+    pub fn sweeps(&self) -> bool {
+        *self != Enforcement::None
+    }
+}
+
+/// The body of `/admin/retention`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport<'a> {
+    pub enforcement: Enforcement,
+    #[serde(flatten)]
+    pub policy: &'a RetentionPolicy,
+}
+
+impl RetentionPolicy {
+    /// The policy of a configuration whose durations `Config::validate` has accepted.
+    pub fn from_config(config: &RetentionConfig) -> Self {
+        RetentionPolicy {
+            default: config.default.as_deref().and_then(parse_duration),
+            measurements: config.measurements
+                .iter()
+                .filter_map(|(measurement, window)| Some((measurement.clone(), parse_duration(window)?)))
+                .collect(),
+            sweep_interval: parse_duration(&config.sweep_interval).unwrap_or(3_600),
+        }
+    }
+
+    /// Whether any reading ever expires.
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.measurements.is_empty()
+    }
+
+    pub fn enforcement(&self, expires_natively: bool) -> Enforcement {
+        match (self.is_empty(), expires_natively) {
+            (true, _) => Enforcement::None,
+            (false, true) => Enforcement::NativeAndSweeper,
+            (false, false) => Enforcement::Sweeper,
+        }
+    }
+
+    pub fn report(&self, expires_natively: bool) -> RetentionReport<'_> {
+        RetentionReport { enforcement: self.enforcement(expires_natively), policy: self }
+    }
+
+    /// The window of a measurement, or `None` when its readings are kept forever.
+    pub fn window(&self, measurement: &str) -> Option<i64> {
+        self.measurements.get(measurement).copied().or(self.default)
+    }
+
+    /// When the reading expires, in seconds since the epoch.
+    pub fn expires_at(&self, reading: &SensorReading) -> Option<i64> {
+        self.window(&reading.measurement).map(|window| reading.recorded.saturating_add(window))
+    }
+
+    /// Whether the reading has expired by `now`; it has from `expires_at` itself on,
+    /// the boundary every backend's sweep and native TTL share.
+    pub fn is_expired(&self, reading: &SensorReading, now: i64) -> bool {
+        self.expires_at(reading).is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// The shortest window of any measurement; nothing recorded since `now` minus it has expired.
+    pub fn shortest_window(&self) -> Option<i64> {
+        self.measurements.values().copied().chain(self.default).min()
+    }
+}
+
+/// Delete expired readings every `sweep_interval`, including ones a native TTL set under an older policy misses.
+pub async fn run_sweeper(access: Arc<dyn DynSensorDataAccess>, policy: RetentionPolicy) {
+    let mut interval = tokio::time::interval(Duration::from_secs(policy.sweep_interval as u64));
+    loop {
+        interval.tick().await;
+        match access.expire_sensor_data(&policy, now()).await {
+            Ok(0) => {}
+            Ok(removed) => println!("Retention sweep removed {} expired readings", removed),
+            Err(e) => eprintln!("Retention sweep failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::migrations::{AppliedMigration, Migration};
+use crate::retention::RetentionPolicy;
+use crate::sensor_query::{SensorPage, SensorQuery};
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::sensor_stats::{aggregate_stream, Bucket, SensorStats};
+use crate::storage_error::StorageResult;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::future::Future;
+
+/// How many readings a streaming read buffers ahead of a slow consumer.
+pub const STREAM_BUFFER: usize = 256;
+
+/// Readings written per statement by bulk writes, keeping multi-row INSERTs under bind limits.
+pub const BATCH_CHUNK: usize = 1000;
+
+/// Readings delivered in order as the backend produces them, ending early on the first error.
+pub type SensorReadingStream = tokio::sync::mpsc::Receiver<StorageResult<SensorReading>>;
+
+/// What `log_sensor_data` did with a reading; the first reading logged under a key wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogOutcome {
+    /// Nothing was stored under the reading's key, so it was stored.
+    Created,
+    /// A reading was already stored under the key, such as a retried post, and was left as is.
+    Duplicate,
+}
+
+/// Readings of a batch that still need writing: the first of each key not already stored.
+pub fn new_readings<'a>(readings: &'a [SensorReading], stored: &HashSet<ReadingKey>) -> Vec<&'a SensorReading> {
+    let mut seen = HashSet::new();
+    readings.iter()
+        .filter(|reading| {
+            let key = reading.key();
+            !stored.contains(&key) && seen.insert(key)
+        })
+        .collect()
+}
+
+/// Outcome of each reading of a batch given the keys it created; later copies of a created key are duplicates.
+pub fn batch_outcomes(readings: &[SensorReading], mut created: HashSet<ReadingKey>) -> Vec<LogOutcome> {
+    readings.iter()
+        .map(|reading| if created.remove(&reading.key()) { LogOutcome::Created } else { LogOutcome::Duplicate })
+        .collect()
+}
+
+/// A storage backend; implementations write these methods as plain `async fn`s.
+pub trait SensorDataAccess: Send + Sync {
+    /// Store the reading unless one is already stored under its location, sensor and recorded time.
+    fn log_sensor_data(&self, reading: &SensorReading) -> impl Future<Output = StorageResult<LogOutcome>> + Send;
+    /// Log every reading as `log_sensor_data` would, in as few round trips as the backend allows,
+    /// returning one outcome per reading in order.
+    fn log_sensor_data_batch(&self, readings: &[SensorReading]) -> impl Future<Output = StorageResult<Vec<LogOutcome>>> + Send;
+    fn fetch_sensor_data(&self, query: &SensorQuery) -> impl Future<Output = StorageResult<SensorPage>> + Send;
+    /// Stream every reading matching the query's filters; `limit` and `cursor` are ignored.
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream;
+    fn purge_sensor_data(&self) -> impl Future<Output = StorageResult<()>> + Send;
+    /// Count, min, max and average of the readings matching the query's filters, per bucket and series;
+    /// `limit` and `cursor` are ignored. Backends that cannot group on the server fold the stream in process.
+    fn aggregate_sensor_data(&self, query: &SensorQuery, bucket: Bucket) -> impl Future<Output = StorageResult<Vec<SensorStats>>> + Send {
+        aggregate_stream(self.stream_sensor_data(query), bucket)
+    }
+    /// Check the backend answers, with the cheapest round trip it has.
+    fn ping(&self) -> impl Future<Output = StorageResult<()>> + Send;
+    /// The reading stored under the key, or `StorageError::NotFound`.
+    fn get_sensor_reading(&self, key: &ReadingKey) -> impl Future<Output = StorageResult<SensorReading>> + Send;
+    /// Remove the reading stored under the key, or fail with `StorageError::NotFound` if there is none.
+    fn delete_sensor_reading(&self, key: &ReadingKey) -> impl Future<Output = StorageResult<()>> + Send;
+
+    /// Whether the backend expires readings on its own as they are written, so no sweeper is needed.
+    fn expires_natively(&self) -> bool {
+        false
+    }
+    /// Delete every reading the policy has expired as of `now`, returning how many were removed.
+    fn expire_sensor_data(&self, _policy: &RetentionPolicy, _now: i64) -> impl Future<Output = StorageResult<u64>> + Send {
+        async { Ok(0) }
+    }
+
+    /// Versioned schema changes for this backend, oldest first; empty when it has no schema.
+    fn migrations(&self) -> &'static [Migration] {
+        &[]
+    }
+    /// The migration's script with configured names filled in.
+    fn migration_script(&self, migration: &Migration) -> String {
+        migration.script.to_string()
+    }
+    /// Migrations recorded in the backend's migrations table, or none when it does not exist yet.
+    /// Only reads, since readiness checks call it on every request.
+    fn applied_migrations(&self) -> impl Future<Output = StorageResult<Vec<AppliedMigration>>> + Send {
+        async { Ok(Vec::new()) }
+    }
+    /// Run one migration's script and record its version, creating the migrations table if needed.
+    fn apply_migration(&self, _migration: &Migration) -> impl Future<Output = StorageResult<()>> + Send {
+        async { Ok(()) }
+    }
+}
+
+/// Object-safe view of [`SensorDataAccess`], so the backend chosen at startup can be
+/// shared as `Arc<dyn DynSensorDataAccess>`. Every backend gets it through the blanket impl.
+pub trait DynSensorDataAccess: Send + Sync {
+    fn log_sensor_data<'a>(&'a self, reading: &'a SensorReading) -> BoxFuture<'a, StorageResult<LogOutcome>>;
+    fn log_sensor_data_batch<'a>(&'a self, readings: &'a [SensorReading]) -> BoxFuture<'a, StorageResult<Vec<LogOutcome>>>;
+    fn fetch_sensor_data<'a>(&'a self, query: &'a SensorQuery) -> BoxFuture<'a, StorageResult<SensorPage>>;
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream;
+    fn purge_sensor_data(&self) -> BoxFuture<'_, StorageResult<()>>;
+    fn aggregate_sensor_data<'a>(&'a self, query: &'a SensorQuery, bucket: Bucket) -> BoxFuture<'a, StorageResult<Vec<SensorStats>>>;
+    fn ping(&self) -> BoxFuture<'_, StorageResult<()>>;
+    fn get_sensor_reading<'a>(&'a self, key: &'a ReadingKey) -> BoxFuture<'a, StorageResult<SensorReading>>;
+    fn delete_sensor_reading<'a>(&'a self, key: &'a ReadingKey) -> BoxFuture<'a, StorageResult<()>>;
+    fn expires_natively(&self) -> bool;
+    fn expire_sensor_data<'a>(&'a self, policy: &'a RetentionPolicy, now: i64) -> BoxFuture<'a, StorageResult<u64>>;
+    fn migrations(&self) -> &'static [Migration];
+    fn migration_script(&self, migration: &Migration) -> String;
+    fn applied_migrations(&self) -> BoxFuture<'_, StorageResult<Vec<AppliedMigration>>>;
+    fn apply_migration<'a>(&'a self, migration: &'a Migration) -> BoxFuture<'a, StorageResult<()>>;
+}
+
+impl<T: SensorDataAccess> DynSensorDataAccess for T {
+    fn log_sensor_data<'a>(&'a self, reading: &'a SensorReading) -> BoxFuture<'a, StorageResult<LogOutcome>> {
+        Box::pin(SensorDataAccess::log_sensor_data(self, reading))
+    }
+
+    fn log_sensor_data_batch<'a>(&'a self, readings: &'a [SensorReading]) -> BoxFuture<'a, StorageResult<Vec<LogOutcome>>> {
+        Box::pin(SensorDataAccess::log_sensor_data_batch(self, readings))
+    }
+
+    fn fetch_sensor_data<'a>(&'a self, query: &'a SensorQuery) -> BoxFuture<'a, StorageResult<SensorPage>> {
+        Box::pin(SensorDataAccess::fetch_sensor_data(self, query))
+    }
+
+    fn stream_sensor_data(&self, query: &SensorQuery) -> SensorReadingStream {
+        SensorDataAccess::stream_sensor_data(self, query)
+    }
+
+    fn purge_sensor_data(&self) -> BoxFuture<'_, StorageResult<()>> {
+        Box::pin(SensorDataAccess::purge_sensor_data(self))
+    }
+
+    fn aggregate_sensor_data<'a>(&'a self, query: &'a SensorQuery, bucket: Bucket) -> BoxFuture<'a, StorageResult<Vec<SensorStats>>> {
+        Box::pin(SensorDataAccess::aggregate_sensor_data(self, query, bucket))
+    }
+
+    fn ping(&self) -> BoxFuture<'_, StorageResult<()>> {
+        Box::pin(SensorDataAccess::ping(self))
+    }
+
+    fn get_sensor_reading<'a>(&'a self, key: &'a ReadingKey) -> BoxFuture<'a, StorageResult<SensorReading>> {
+        Box::pin(SensorDataAccess::get_sensor_reading(self, key))
+    }
+
+    fn delete_sensor_reading<'a>(&'a self, key: &'a ReadingKey) -> BoxFuture<'a, StorageResult<()>> {
+        Box::pin(SensorDataAccess::delete_sensor_reading(self, key))
+    }
+
+    fn expires_natively(&self) -> bool {
+        SensorDataAccess::expires_natively(self)
+    }
+
+    fn expire_sensor_data<'a>(&'a self, policy: &'a RetentionPolicy, now: i64) -> BoxFuture<'a, StorageResult<u64>> {
+        Box::pin(SensorDataAccess::expire_sensor_data(self, policy, now))
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        SensorDataAccess::migrations(self)
+    }
+
+    fn migration_script(&self, migration: &Migration) -> String {
+        SensorDataAccess::migration_script(self, migration)
+    }
+
+    fn applied_migrations(&self) -> BoxFuture<'_, StorageResult<Vec<AppliedMigration>>> {
+        Box::pin(SensorDataAccess::applied_migrations(self))
+    }
+
+    fn apply_migration<'a>(&'a self, migration: &'a Migration) -> BoxFuture<'a, StorageResult<()>> {
+        Box::pin(SensorDataAccess::apply_migration(self, migration))
+    }
+}
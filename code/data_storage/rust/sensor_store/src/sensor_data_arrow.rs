@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_data_access_trait::SensorReadingStream;
+use crate::sensor_data_csv::CSV_HEADER;
+use crate::sensor_reading::SensorReading;
+use crate::storage_error::{StorageError, StorageResult};
+use arrow::array::{ArrayRef, Float64Array, Int64Array, StringDictionaryBuilder};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, SchemaRef};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use std::error::Error;
+use std::sync::Arc;
+
+/// A columnar export format for `/export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Arrow,
+}
+
+impl ExportFormat {
+    /// Parse the `format=` query parameter; parquet when absent.
+    pub fn parse(format: Option<&str>) -> Result<Self, String> {
+        match format.map(|f| f.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("parquet") => Ok(ExportFormat::Parquet),
+            Some("arrow") => Ok(ExportFormat::Arrow),
+            Some(other) => Err(format!("Unknown export format '{}'; supported: parquet, arrow", other)),
+        }
+    }
+
+    /// File extension, also used in the download file name.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Arrow => "arrow",
+        }
+    }
+
+    /// Value of the `Content-Type` header.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+            ExportFormat::Arrow => "application/vnd.apache.arrow.file",
+        }
+    }
+}
+
+/// Columns in `CSV_HEADER` order: i64 `recorded`, dictionary-encoded strings, f64 `value`.
+pub fn sensor_schema() -> SchemaRef {
+    let dictionary = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+    Arc::new(Schema::new(vec![
+        Field::new(CSV_HEADER[0], DataType::Int64, false),
+        Field::new(CSV_HEADER[1], dictionary.clone(), false),
+        Field::new(CSV_HEADER[2], dictionary.clone(), false),
+        Field::new(CSV_HEADER[3], dictionary.clone(), false),
+        Field::new(CSV_HEADER[4], dictionary, false),
+        Field::new(CSV_HEADER[5], DataType::Float64, false),
+    ]))
+}
+
+fn dictionary_column<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+    let mut builder = StringDictionaryBuilder::<Int32Type>::new();
+    for value in values {
+        builder.append_value(value);
+    }
+    Arc::new(builder.finish())
+}
+
+/// One record batch holding every reading.
+pub fn readings_to_record_batch(readings: &[SensorReading]) -> Result<RecordBatch, Box<dyn Error + Send + Sync>> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from_iter_values(readings.iter().map(|r| r.recorded))),
+        dictionary_column(readings.iter().map(|r| r.location.as_str())),
+        dictionary_column(readings.iter().map(|r| r.sensor.as_str())),
+        dictionary_column(readings.iter().map(|r| r.measurement.as_str())),
+        dictionary_column(readings.iter().map(|r| r.units.as_str())),
+        Arc::new(Float64Array::from_iter_values(readings.iter().map(|r| r.value))),
+    ];
+    Ok(RecordBatch::try_new(sensor_schema(), columns)?)
+}
+
+/// A complete Parquet or Arrow IPC file for the readings.
+pub fn export_readings(format: ExportFormat, readings: &[SensorReading]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let batch = readings_to_record_batch(readings)?;
+    match format {
+        ExportFormat::Parquet => {
+            let mut writer = ArrowWriter::try_new(Vec::new(), batch.schema(), None)?;
+            writer.write(&batch)?;
+            Ok(writer.into_inner()?)
+        }
+        ExportFormat::Arrow => {
+            let mut writer = FileWriter::try_new(Vec::new(), &batch.schema())?;
+            writer.write(&batch)?;
+            writer.finish()?;
+            Ok(writer.into_inner()?)
+        }
+    }
+}
+
+/// Most readings one export may hold. Arrow IPC files cannot change a dictionary between
+/// batches, so an export is built in memory and larger result sets must be narrowed.
+pub const MAX_EXPORT_READINGS: usize = 100_000;
+
+/// Drain a reading stream, for formats that need the whole result set before writing.
+/// Fails with a validation error as soon as the stream yields more than `max` readings.
+pub async fn collect_readings(mut rows: SensorReadingStream, max: usize) -> StorageResult<Vec<SensorReading>> {
+    let mut readings = Vec::new();
+    while let Some(row) = rows.recv().await {
+        if readings.len() == max {
+            return Err(StorageError::Validation(format!(
+                "more than {} readings match; narrow the time range or filters",
+                max
+            )));
+        }
+        readings.push(row?);
+    }
+    Ok(readings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_data_access::MemoryDataAccess;
+    use crate::sensor_data_access_trait::SensorDataAccess;
+    use crate::sensor_query::SensorQuery;
+
+    fn reading(recorded: i64) -> SensorReading {
+        SensorReading {
+            recorded,
+            location: "den".to_string(),
+            sensor: "bmp280".to_string(),
+            measurement: "temperature".to_string(),
+            units: "C".to_string(),
+            value: 21.5,
+        }
+    }
+
+    #[tokio::test]
+    async fn exports_stop_past_the_reading_limit() {
+        let store = MemoryDataAccess::new();
+        for offset in 0..3 {
+            store.log_sensor_data(&reading(1_756_000_000 + offset)).await.unwrap();
+        }
+
+        let readings = collect_readings(store.stream_sensor_data(&SensorQuery::default()), 3).await.unwrap();
+        assert_eq!(readings.len(), 3);
+        let file = export_readings(ExportFormat::Arrow, &readings).unwrap();
+        assert!(file.starts_with(b"ARROW1"));
+
+        let error = collect_readings(store.stream_sensor_data(&SensorQuery::default()), 2).await.unwrap_err();
+        assert!(matches!(error, StorageError::Validation(message) if message.contains("more than 2 readings")));
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_data_access_trait::{DynSensorDataAccess, LogOutcome};
+use crate::sensor_data_json_helper::ValidationErrors;
+use crate::sensor_reading::SensorReading;
+use crate::storage_error::StorageResult;
+use serde::Serialize;
+use serde_json::Value;
+
+/// One item of an uploaded batch: a reading to log, or why it was rejected.
+pub type BatchItem = Result<SensorReading, ValidationErrors>;
+
+/// What happened to the item at `index` of the batch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BatchResult {
+    Accepted { index: usize, outcome: LogOutcome },
+    Rejected { index: usize, errors: ValidationErrors },
+}
+
+/// The response to a batch upload, one result per item in upload order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchReport {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub results: Vec<BatchResult>,
+}
+
+/// Split a batch body into items, validating each one on its own.
+///
+/// A body starting with `[` is a JSON array of readings; anything else is NDJSON with
+/// one reading per line, where blank lines are skipped and do not count as items.
+pub fn parse_batch(body: &[u8]) -> Result<Vec<BatchItem>, String> {
+    if body.trim_ascii_start().starts_with(b"[") {
+        let values: Vec<Value> = serde_json::from_slice(body).map_err(|e| format!("Invalid JSON: {}", e))?;
+        return Ok(values.iter().map(|value| SensorReading::from_json(&value.to_string())).collect());
+    }
+    let body = std::str::from_utf8(body).map_err(|e| format!("Invalid NDJSON: {}", e))?;
+    Ok(body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(SensorReading::from_json)
+        .collect())
+}
+
+/// Log the valid items with one bulk write and report every item by its index.
+pub async fn log_batch(access: &dyn DynSensorDataAccess, items: Vec<BatchItem>) -> StorageResult<BatchReport> {
+    let mut readings = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        match item {
+            Ok(reading) => readings.push((index, reading)),
+            Err(errors) => rejected.push(BatchResult::Rejected { index, errors }),
+        }
+    }
+
+    let (indexes, readings): (Vec<usize>, Vec<SensorReading>) = readings.into_iter().unzip();
+    let outcomes = access.log_sensor_data_batch(&readings).await?;
+    let mut results: Vec<BatchResult> = indexes
+        .into_iter()
+        .zip(outcomes)
+        .map(|(index, outcome)| BatchResult::Accepted { index, outcome })
+        .collect();
+    let accepted = results.len();
+    let rejected_count = rejected.len();
+    results.extend(rejected);
+    results.sort_by_key(|result| match result {
+        BatchResult::Accepted { index, .. } | BatchResult::Rejected { index, .. } => *index,
+    });
+
+    Ok(BatchReport { accepted, rejected: rejected_count, results })
+}
@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::fanout_data_access::NamedStore;
+use crate::sensor_data_access_trait::{DynSensorDataAccess, LogOutcome};
+use crate::sensor_query::SensorQuery;
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::storage_error::{StorageError, StorageResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+
+/// How far a copy got, saved after every batch the destination accepted.
+///
+/// Readings are written in `(recorded, location, sensor)` order, so the key of the last one
+/// written marks everything before it as copied: a resumed copy streams from its `recorded`
+/// value and skips keys up to it. Logging is idempotent, so readings written twice are only
+/// counted as duplicates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CopyCheckpoint {
+    pub source: String,
+    pub destination: String,
+    /// Key of the last reading written, or none before the first batch.
+    pub last: Option<ReadingKey>,
+    /// Readings the destination stored.
+    pub created: u64,
+    /// Readings the destination already held.
+    pub duplicates: u64,
+}
+
+impl CopyCheckpoint {
+    fn new(source: &str, destination: &str) -> Self {
+        CopyCheckpoint { source: source.to_string(), destination: destination.to_string(), ..Default::default() }
+    }
+
+    /// The checkpoint in the file, or a fresh one when there is no file or no file yet.
+    fn load(path: Option<&Path>, source: &str, destination: &str) -> StorageResult<Self> {
+        let Some(path) = path else {
+            return Ok(CopyCheckpoint::new(source, destination));
+        };
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(CopyCheckpoint::new(source, destination)),
+            Err(e) => return Err(StorageError::Backend(format!("Failed to read checkpoint {}: {}", path.display(), e))),
+        };
+        let checkpoint: CopyCheckpoint = serde_json::from_str(&json)
+            .map_err(|e| StorageError::Validation(format!("Invalid checkpoint {}: {}", path.display(), e)))?;
+        if checkpoint.source != source || checkpoint.destination != destination {
+            return Err(StorageError::Validation(format!(
+                "Checkpoint {} is for a copy from {} to {}",
+                path.display(), checkpoint.source, checkpoint.destination
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    // Written beside the file and renamed over it, so an interrupted save keeps the last one
+    fn save(&self, path: &Path) -> StorageResult<()> {
+        let partial = path.with_extension("partial");
+        let json = serde_json::to_string_pretty(self).expect("CopyCheckpoint is always serializable");
+        fs::write(&partial, json)
+            .and_then(|_| fs::rename(&partial, path))
+            .map_err(|e| StorageError::Backend(format!("Failed to write checkpoint {}: {}", path.display(), e)))
+    }
+
+    /// The source readings a copy resumes from: every one recorded at or after the last written.
+    fn resume_query(&self) -> SensorQuery {
+        SensorQuery { recorded_from: self.last.as_ref().map(|last| last.recorded), ..SensorQuery::default() }
+    }
+
+    /// Whether the reading sorts after the last one written, so it is still to be copied.
+    fn is_pending(&self, reading: &SensorReading) -> bool {
+        self.last.as_ref().is_none_or(|last| copy_order(reading) > (last.recorded, &last.location, &last.sensor))
+    }
+}
+
+// The order a copy writes readings in, which a checkpoint's `last` key is compared by
+fn copy_order(reading: &SensorReading) -> (i64, &String, &String) {
+    (reading.recorded, &reading.location, &reading.sensor)
+}
+
+/// Row count and order-independent checksum of every reading in a store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub count: u64,
+    pub checksum: u64,
+}
+
+impl Tally {
+    fn add(&mut self, reading: &SensorReading) {
+        self.count += 1;
+        self.checksum = self.checksum.wrapping_add(reading_hash(reading));
+    }
+}
+
+// Hash of every field, comparable between stores within one run
+fn reading_hash(reading: &SensorReading) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    reading.location.hash(&mut hasher);
+    reading.sensor.hash(&mut hasher);
+    reading.recorded.hash(&mut hasher);
+    reading.measurement.hash(&mut hasher);
+    reading.units.hash(&mut hasher);
+    reading.value.to_bits().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Count and checksum every reading the store streams.
+pub async fn tally(access: &dyn DynSensorDataAccess) -> StorageResult<Tally> {
+    let mut rows = access.stream_sensor_data(&SensorQuery::default());
+    let mut tally = Tally::default();
+    while let Some(reading) = rows.recv().await {
+        tally.add(&reading?);
+    }
+    Ok(tally)
+}
+
+/// Log one batch to the destination, count what it did and save the checkpoint after it.
+async fn write_batch(destination: &dyn DynSensorDataAccess, batch: &[SensorReading], checkpoint: &mut CopyCheckpoint, checkpoint_path: Option<&Path>) -> StorageResult<()> {
+    for outcome in destination.log_sensor_data_batch(batch).await? {
+        match outcome {
+            LogOutcome::Created => checkpoint.created += 1,
+            LogOutcome::Duplicate => checkpoint.duplicates += 1,
+        }
+    }
+    checkpoint.last = batch.last().map(SensorReading::key);
+    match checkpoint_path {
+        Some(path) => checkpoint.save(path),
+        None => Ok(()),
+    }
+}
+
+/// Stream every reading of the source into the destination in batches, resuming from
+/// the checkpoint file when one is given and saving progress to it after each batch.
+///
+/// Backends stream readings in `recorded` order but break ties in their own way, so the
+/// readings sharing a `recorded` value are sorted by key before they are batched. A
+/// checkpointed copy fails on a source that streams out of time order, since its
+/// checkpoint could not tell which earlier readings were copied.
+pub async fn copy_readings(source: &NamedStore, destination: &NamedStore, batch_size: usize, checkpoint_path: Option<&Path>) -> StorageResult<CopyCheckpoint> {
+    let mut checkpoint = CopyCheckpoint::load(checkpoint_path, &source.0, &destination.0)?;
+    if let Some(last) = &checkpoint.last {
+        println!("Resuming after {}", last);
+    }
+
+    let mut rows = source.1.stream_sensor_data(&checkpoint.resume_query());
+    let mut tied: Vec<SensorReading> = Vec::new();
+    let mut batch = Vec::with_capacity(batch_size);
+    loop {
+        let reading = rows.recv().await.transpose()?;
+        // A reading recorded at another time, or the end of the stream, closes the run of ties
+        let ends_ties = tied.first().is_some_and(|first| reading.as_ref().is_none_or(|r| r.recorded != first.recorded));
+        if ends_ties {
+            let recorded = tied[0].recorded;
+            if let Some(reading) = reading.as_ref().filter(|r| r.recorded < recorded && checkpoint_path.is_some()) {
+                return Err(StorageError::Validation(format!(
+                    "{} streamed a reading recorded at {} after one at {}, so the copy cannot be checkpointed",
+                    source.0, reading.recorded, recorded
+                )));
+            }
+            tied.sort_by(|a, b| copy_order(a).cmp(&copy_order(b)));
+            for reading in tied.drain(..) {
+                batch.push(reading);
+                if batch.len() == batch_size {
+                    write_batch(&*destination.1, &batch, &mut checkpoint, checkpoint_path).await?;
+                    batch.clear();
+                    println!("Copied {} readings, {} already present", checkpoint.created, checkpoint.duplicates);
+                }
+            }
+        }
+        match reading {
+            Some(reading) if checkpoint.is_pending(&reading) => tied.push(reading),
+            Some(_) => {}
+            None => break,
+        }
+    }
+    if !batch.is_empty() {
+        write_batch(&*destination.1, &batch, &mut checkpoint, checkpoint_path).await?;
+    }
+    Ok(checkpoint)
+}
+
+/// The `copy` command: count what a copy would read, or copy and compare both stores.
+/// Returns whether the stores ended up with the same count and checksum; a dry run always does.
+pub async fn run_copy(source: &NamedStore, destination: &NamedStore, batch_size: usize, checkpoint_path: Option<&Path>, dry_run: bool) -> StorageResult<bool> {
+    if dry_run {
+        let checkpoint = CopyCheckpoint::load(checkpoint_path, &source.0, &destination.0)?;
+        let mut rows = source.1.stream_sensor_data(&checkpoint.resume_query());
+        let mut pending = 0u64;
+        while let Some(reading) = rows.recv().await {
+            if checkpoint.is_pending(&reading?) {
+                pending += 1;
+            }
+        }
+        println!("Would copy {} readings from {} to {}", pending, source.0, destination.0);
+        return Ok(true);
+    }
+    let checkpoint = copy_readings(source, destination, batch_size, checkpoint_path).await?;
+    println!(
+        "Copied {} readings from {} to {}, {} already present",
+        checkpoint.created, source.0, destination.0, checkpoint.duplicates
+    );
+
+    let (from, to) = (tally(&*source.1).await?, tally(&*destination.1).await?);
+    for ((name, _), tally) in [(source, from), (destination, to)] {
+        println!("{:<12} {:>10} readings  checksum {:016x}", name, tally.count, tally.checksum);
+    }
+    if from == to {
+        println!("Counts and checksums match");
+    } else {
+        println!("Counts or checksums differ");
+    }
+    Ok(from == to)
+}
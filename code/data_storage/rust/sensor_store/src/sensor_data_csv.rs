@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+/// Column order of every CSV report; matches the field order of `SensorReading`.
+pub const CSV_HEADER: [&str; 6] = ["recorded", "location", "sensor", "measurement", "units", "value"];
@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use serde::Serialize;
+use serde_json::{Value};
+use log::{error};
+use std::fmt;
+
+/// Earliest accepted `recorded` timestamp (2000-01-01T00:00:00Z).
+pub const MIN_RECORDED: i64 = 946_684_800;
+/// Latest accepted `recorded` timestamp (2100-01-01T00:00:00Z).
+pub const MAX_RECORDED: i64 = 4_102_444_800;
+
+/// A single problem found while validating a sensor reading.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        FieldError { field: field.to_string(), message: message.into() }
+    }
+}
+
+/// Every problem found in a sensor reading, in field order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.0.iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Parse a sensor JSON string and check every field's type and range.
+///
+/// All violations are collected so callers can report them at once.
+pub fn validate_sensor_json(json_str: &str) -> Result<Value, ValidationErrors> {
+    let parsed: Value = serde_json::from_str(json_str)
+        .map_err(|e| ValidationErrors(vec![FieldError::new("body", format!("JSON parse error: {}", e))]))?;
+    if !parsed.is_object() {
+        return Err(ValidationErrors(vec![FieldError::new("body", "must be a JSON object")]));
+    }
+
+    let mut errors = Vec::new();
+    match parsed.get("recorded") {
+        None => errors.push(FieldError::new("recorded", "is required")),
+        Some(v) => match as_integer(v) {
+            None => errors.push(FieldError::new("recorded", "must be an integer epoch timestamp")),
+            Some(recorded) if !(MIN_RECORDED..=MAX_RECORDED).contains(&recorded) => errors.push(FieldError::new(
+                "recorded",
+                format!("must be between {} and {}", MIN_RECORDED, MAX_RECORDED),
+            )),
+            Some(_) => {}
+        },
+    }
+    for (field, max_len) in [("location", 255), ("sensor", 255), ("measurement", 255), ("units", 50)] {
+        match parsed.get(field) {
+            None => errors.push(FieldError::new(field, "is required")),
+            Some(Value::String(s)) if s.trim().is_empty() => errors.push(FieldError::new(field, "must not be empty")),
+            Some(Value::String(s)) if s.chars().count() > max_len => errors.push(FieldError::new(
+                field,
+                format!("must be at most {} characters", max_len),
+            )),
+            Some(Value::String(_)) => {}
+            Some(_) => errors.push(FieldError::new(field, "must be a string")),
+        }
+    }
+    match parsed.get("value") {
+        None => errors.push(FieldError::new("value", "is required")),
+        Some(v) => match as_number(v) {
+            None => errors.push(FieldError::new("value", "must be a number")),
+            Some(value) if !value.is_finite() => errors.push(FieldError::new("value", "must be finite")),
+            Some(_) => {}
+        },
+    }
+
+    if errors.is_empty() {
+        Ok(parsed)
+    } else {
+        error!("Invalid sensor data: {}", ValidationErrors(errors.clone()));
+        Err(ValidationErrors(errors))
+    }
+}
+
+/// Read an integer, accepting integer strings for older devices.
+pub fn as_integer(v: &Value) -> Option<i64> {
+    v.as_i64().or_else(|| v.as_str().and_then(|s| s.trim().parse::<i64>().ok()))
+}
+
+/// Read a number, accepting numeric strings for older devices.
+pub fn as_number(v: &Value) -> Option<f64> {
+    v.as_f64().or_else(|| v.as_str().and_then(|s| s.trim().parse::<f64>().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(errors: &ValidationErrors) -> Vec<&str> {
+        errors.0.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_reading() {
+        let json = r#"{"recorded":1700000000,"location":"den","sensor":"bmp280","measurement":"temperature","units":"C","value":22.3}"#;
+        assert!(validate_sensor_json(json).is_ok());
+    }
+
+    #[test]
+    fn reports_every_violation_at_once() {
+        let json = r#"{"recorded":"soon","location":"","sensor":7,"units":"C","value":"warm"}"#;
+        let errors = validate_sensor_json(json).unwrap_err();
+        assert_eq!(fields(&errors), ["recorded", "location", "sensor", "measurement", "value"]);
+        assert_eq!(errors.0[0].message, "must be an integer epoch timestamp");
+        assert_eq!(errors.0[1].message, "must not be empty");
+        assert_eq!(errors.0[2].message, "must be a string");
+        assert_eq!(errors.0[3].message, "is required");
+        assert_eq!(errors.0[4].message, "must be a number");
+    }
+
+    #[test]
+    fn checks_ranges_and_lengths() {
+        let json = format!(
+            r#"{{"recorded":{},"location":"den","sensor":"{}","measurement":"temperature","units":"{}","value":1}}"#,
+            MAX_RECORDED + 1,
+            "s".repeat(256),
+            "u".repeat(51),
+        );
+        let errors = validate_sensor_json(&json).unwrap_err();
+        assert_eq!(fields(&errors), ["recorded", "sensor", "units"]);
+        assert_eq!(errors.0[1].message, "must be at most 255 characters");
+        assert_eq!(errors.0[2].message, "must be at most 50 characters");
+    }
+
+    #[test]
+    fn accepts_numeric_strings_from_older_devices() {
+        let json = r#"{"recorded":"1700000000","location":"den","sensor":"bmp280","measurement":"temperature","units":"C","value":" 22.3 "}"#;
+        assert!(validate_sensor_json(json).is_ok());
+    }
+
+    #[test]
+    fn rejects_bodies_that_are_not_objects() {
+        let errors = validate_sensor_json("[1, 2]").unwrap_err();
+        assert_eq!(fields(&errors), ["body"]);
+        let errors = validate_sensor_json("{").unwrap_err();
+        assert!(errors.0[0].message.starts_with("JSON parse error"));
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::fanout_data_access::NamedStore;
+use crate::sensor_data_access_trait::DynSensorDataAccess;
+use crate::sensor_query::SensorQuery;
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::storage_error::{StorageError, StorageResult};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// How one store's readings compare with the reference store's.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DriftSummary {
+    pub store: String,
+    pub reference: String,
+    /// Readings the reference store holds.
+    pub expected: u64,
+    /// Readings this store holds.
+    pub readings: u64,
+    pub missing: u64,
+    pub extra: u64,
+    pub mismatched: u64,
+}
+
+impl DriftSummary {
+    pub fn has_drift(&self) -> bool {
+        self.missing + self.extra + self.mismatched > 0
+    }
+}
+
+/// One line of the diff, tagged with its `kind`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Drift<'a> {
+    /// The reference store holds a reading this store does not.
+    Missing { store: &'a str, key: ReadingKey, expected: &'a SensorReading },
+    /// This store holds a reading the reference store does not.
+    Extra { store: &'a str, key: ReadingKey, found: &'a SensorReading },
+    /// Both stores hold a reading under the key, with different fields.
+    Mismatched { store: &'a str, key: ReadingKey, expected: &'a SensorReading, found: &'a SensorReading },
+    /// The counts for one store, after its differences.
+    Summary(&'a DriftSummary),
+}
+
+fn write_drift(out: &mut impl Write, drift: &Drift) -> StorageResult<()> {
+    serde_json::to_writer(&mut *out, drift)
+        .map_err(std::io::Error::from)
+        .and_then(|_| out.write_all(b"\n"))
+        .map_err(|e| StorageError::Backend(format!("Failed to write diff: {}", e)))
+}
+
+/// Every reading of the store by its key.
+async fn readings_by_key(access: &dyn DynSensorDataAccess) -> StorageResult<HashMap<ReadingKey, SensorReading>> {
+    let mut rows = access.stream_sensor_data(&SensorQuery::default());
+    let mut readings = HashMap::new();
+    while let Some(reading) = rows.recv().await {
+        let reading = reading?;
+        readings.insert(reading.key(), reading);
+    }
+    Ok(readings)
+}
+
+/// Compare every store with the first, the reference, writing each difference to `out`
+/// as a line of JSON followed by a summary line per store.
+///
+/// Only the reference is held in memory; the other stores are streamed past it.
+pub async fn verify_stores(stores: &[NamedStore], out: &mut impl Write) -> StorageResult<Vec<DriftSummary>> {
+    let Some(((reference, access), others)) = stores.split_first() else {
+        return Ok(Vec::new());
+    };
+    let expected = readings_by_key(&**access).await?;
+
+    let mut summaries = Vec::new();
+    for (store, access) in others {
+        let mut summary = DriftSummary {
+            store: store.clone(),
+            reference: reference.clone(),
+            expected: expected.len() as u64,
+            ..Default::default()
+        };
+        let mut seen = HashSet::new();
+        let mut rows = access.stream_sensor_data(&SensorQuery::default());
+        while let Some(found) = rows.recv().await {
+            let found = found?;
+            summary.readings += 1;
+            let key = found.key();
+            match expected.get(&key) {
+                None => {
+                    summary.extra += 1;
+                    write_drift(out, &Drift::Extra { store, key: key.clone(), found: &found })?;
+                }
+                Some(reading) if *reading != found => {
+                    summary.mismatched += 1;
+                    write_drift(out, &Drift::Mismatched { store, key: key.clone(), expected: reading, found: &found })?;
+                }
+                Some(_) => {}
+            }
+            seen.insert(key);
+        }
+
+        // Listed in time order so diffs of the same stores compare line by line
+        let mut missing: Vec<(&ReadingKey, &SensorReading)> = expected.iter().filter(|(key, _)| !seen.contains(*key)).collect();
+        missing.sort_by(|(a, _), (b, _)| (a.recorded, &a.location, &a.sensor).cmp(&(b.recorded, &b.location, &b.sensor)));
+        for (key, reading) in missing {
+            summary.missing += 1;
+            write_drift(out, &Drift::Missing { store, key: key.clone(), expected: reading })?;
+        }
+        write_drift(out, &Drift::Summary(&summary))?;
+        summaries.push(summary);
+    }
+    Ok(summaries)
+}
+
+/// The `verify` subcommand: write the diff to the file and print a summary per store.
+/// Returns whether every store matched the reference.
+pub async fn run_verify(stores: &[NamedStore], diff_path: &Path) -> StorageResult<bool> {
+    let file = File::create(diff_path)
+        .map_err(|e| StorageError::Backend(format!("Failed to create {}: {}", diff_path.display(), e)))?;
+    let mut out = BufWriter::new(file);
+    let summaries = verify_stores(stores, &mut out).await?;
+    out.flush().map_err(|e| StorageError::Backend(format!("Failed to write diff: {}", e)))?;
+
+    for summary in &summaries {
+        println!(
+            "{} against {}: {} of {} readings, {} missing, {} extra, {} mismatched",
+            summary.store, summary.reference, summary.readings, summary.expected,
+            summary.missing, summary.extra, summary.mismatched
+        );
+    }
+    let consistent = !summaries.iter().any(DriftSummary::has_drift);
+    if consistent {
+        println!("Every store matches {}", stores[0].0);
+    } else {
+        println!("Differences written to {}", diff_path.display());
+    }
+    Ok(consistent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_data_access::MemoryDataAccess;
+    use std::sync::Arc;
+
+    fn reading(location: &str, recorded: i64, value: f64) -> SensorReading {
+        SensorReading {
+            recorded,
+            location: location.to_string(),
+            sensor: "bmp280".to_string(),
+            measurement: "temperature".to_string(),
+            units: "C".to_string(),
+            value,
+        }
+    }
+
+    async fn store(name: &str, readings: &[SensorReading]) -> NamedStore {
+        let access: Arc<dyn DynSensorDataAccess> = Arc::new(MemoryDataAccess::new());
+        for reading in readings {
+            access.log_sensor_data(reading).await.unwrap();
+        }
+        (name.to_string(), access)
+    }
+
+    #[tokio::test]
+    async fn reports_missing_extra_and_mismatched_readings() {
+        let stores = [
+            store("postgres", &[reading("den", 10, 1.0), reading("den", 20, 2.0), reading("den", 30, 3.0)]).await,
+            store("mongo", &[reading("den", 10, 1.0), reading("den", 20, 2.5), reading("attic", 40, 4.0)]).await,
+            store("redis", &[reading("den", 10, 1.0), reading("den", 20, 2.0), reading("den", 30, 3.0)]).await,
+        ];
+        let mut out = Vec::new();
+        let summaries = verify_stores(&stores, &mut out).await.unwrap();
+
+        assert_eq!(summaries[0], DriftSummary {
+            store: "mongo".to_string(),
+            reference: "postgres".to_string(),
+            expected: 3,
+            readings: 3,
+            missing: 1,
+            extra: 1,
+            mismatched: 1,
+        });
+        assert!(!summaries[1].has_drift());
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let kinds: Vec<&str> = lines.iter().map(|line| line["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["mismatched", "extra", "missing", "summary", "summary"]);
+        assert_eq!(lines[0]["found"]["value"], 2.5);
+        assert_eq!(lines[1]["key"]["location"], "attic");
+        assert_eq!(lines[2]["expected"]["recorded"], 30);
+    }
+
+    #[tokio::test]
+    async fn a_lone_store_has_nothing_to_compare() {
+        let stores = [store("postgres", &[reading("den", 10, 1.0)]).await];
+        let mut out = Vec::new();
+        assert!(verify_stores(&stores, &mut out).await.unwrap().is_empty());
+        assert!(out.is_empty());
+    }
+}
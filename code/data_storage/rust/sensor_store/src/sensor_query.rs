@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use serde::{Deserialize, Serialize};
+
+/// Largest page a report may ask for; anything bigger streams without a `limit`.
+pub const MAX_PAGE_LIMIT: usize = 10_000;
+
+/// Filters for `SensorDataAccess::fetch_sensor_data`; unset fields match everything.
+///
+/// The `recorded` range is half-open: `from <= recorded < to`. When `limit` is set
+/// the result is paged, and `cursor` carries the token returned with the previous page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SensorQuery {
+    #[serde(rename = "from", skip_serializing_if = "Option::is_none")]
+    pub recorded_from: Option<i64>,
+    #[serde(rename = "to", skip_serializing_if = "Option::is_none")]
+    pub recorded_to: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measurement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+impl SensorQuery {
+    /// Check a reading against every filter, for backends that filter in process.
+    pub fn matches(&self, reading: &SensorReading) -> bool {
+        self.recorded_from.is_none_or(|from| reading.recorded >= from)
+            && self.recorded_to.is_none_or(|to| reading.recorded < to)
+            && self.location.as_ref().is_none_or(|l| *l == reading.location)
+            && self.sensor.as_ref().is_none_or(|s| *s == reading.sensor)
+            && self.measurement.as_ref().is_none_or(|m| *m == reading.measurement)
+    }
+
+    /// Reject page sizes the backends cannot page by: an empty page would never
+    /// move the cursor on, and a huge one overflows their row counts.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.limit {
+            Some(limit) if limit == 0 || limit > MAX_PAGE_LIMIT => {
+                Err(format!("limit must be between 1 and {}", MAX_PAGE_LIMIT))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Every reading stored under the key, for backends that look keys up like any other query.
+    pub fn for_key(key: &ReadingKey) -> SensorQuery {
+        SensorQuery {
+            recorded_from: Some(key.recorded),
+            // No reading sorts after i64::MAX, so leaving `to` open is the same range
+            recorded_to: key.recorded.checked_add(1),
+            location: Some(key.location.clone()),
+            sensor: Some(key.sensor.clone()),
+            ..SensorQuery::default()
+        }
+    }
+
+    /// The same query positioned at the page after `next`.
+    pub fn next_page(&self, next: &str) -> SensorQuery {
+        SensorQuery { cursor: Some(next.to_string()), ..self.clone() }
+    }
+}
+
+/// One page of readings and the continuation token for the page after it.
+///
+/// A page may hold fewer than `limit` readings when a backend filters in process;
+/// only a missing `next` means the results are exhausted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SensorPage {
+    pub readings: Vec<SensorReading>,
+    pub next: Option<String>,
+}
+
+/// Encode a backend-specific position as an opaque, URL-safe continuation token.
+pub fn encode_cursor(position: &[u8]) -> String {
+    position.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode a token produced by `encode_cursor`.
+pub fn decode_cursor(token: &str) -> Result<Vec<u8>, String> {
+    if !token.len().is_multiple_of(2) || !token.is_ascii() {
+        return Err("Invalid cursor".to_string());
+    }
+    (0..token.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&token[i..i + 2], 16).map_err(|_| "Invalid cursor".to_string()))
+        .collect()
+}
+
+/// Encode a keyset position: the last row's `recorded` value and a unique tiebreaker.
+pub fn encode_keyset(recorded: i64, tiebreaker: &str) -> String {
+    encode_cursor(format!("{}:{}", recorded, tiebreaker).as_bytes())
+}
+
+/// Decode a token produced by `encode_keyset`.
+pub fn decode_keyset(token: &str) -> Result<(i64, String), String> {
+    let position = String::from_utf8(decode_cursor(token)?).map_err(|_| "Invalid cursor".to_string())?;
+    let (recorded, tiebreaker) = position.split_once(':').ok_or("Invalid cursor")?;
+    let recorded = recorded.parse::<i64>().map_err(|_| "Invalid cursor".to_string())?;
+    Ok((recorded, tiebreaker.to_string()))
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_data_json_helper::{as_integer, as_number, validate_sensor_json, ValidationErrors};
+use crate::storage_error::StorageError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// A single sensor reading, the one JSON record every backend stores.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorReading {
+    pub recorded: i64,
+    pub location: String,
+    pub sensor: String,
+    pub measurement: String,
+    pub units: String,
+    pub value: f64,
+}
+
+impl SensorReading {
+    /// Parse and validate a JSON string into a reading.
+    pub fn from_json(json_str: &str) -> Result<Self, ValidationErrors> {
+        let parsed = validate_sensor_json(json_str)?;
+        Ok(Self::from_validated(&parsed))
+    }
+
+    /// Build a reading from a JSON object already accepted by `validate_sensor_json`.
+    fn from_validated(parsed: &Value) -> Self {
+        SensorReading {
+            recorded: as_integer(&parsed["recorded"]).unwrap_or_default(),
+            location: string_field(parsed, "location"),
+            sensor: string_field(parsed, "sensor"),
+            measurement: string_field(parsed, "measurement"),
+            units: string_field(parsed, "units"),
+            value: as_number(&parsed["value"]).unwrap_or_default(),
+        }
+    }
+
+    /// The key the reading is stored under.
+    pub fn key(&self) -> ReadingKey {
+        ReadingKey { location: self.location.clone(), sensor: self.sensor.clone(), recorded: self.recorded }
+    }
+
+    /// Serialize the reading as a compact JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("SensorReading is always serializable")
+    }
+}
+
+/// Identifies one reading: what was measured where and when, as in `/readings/{location}/{sensor}/{recorded}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ReadingKey {
+    pub location: String,
+    pub sensor: String,
+    pub recorded: i64,
+}
+
+impl fmt::Display for ReadingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.location, self.sensor, self.recorded)
+    }
+}
+
+impl ReadingKey {
+    /// Whether the reading is stored under this key.
+    pub fn matches(&self, reading: &SensorReading) -> bool {
+        reading.recorded == self.recorded && reading.location == self.location && reading.sensor == self.sensor
+    }
+
+    /// The error reported when nothing is stored under this key.
+    pub fn not_found(&self) -> StorageError {
+        StorageError::NotFound(format!("No reading stored for {}", self))
+    }
+}
+
+fn string_field(parsed: &Value, field: &str) -> String {
+    parsed[field].as_str().unwrap_or_default().to_string()
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::sensor_data_access_trait::SensorReadingStream;
+use crate::sensor_reading::SensorReading;
+use crate::storage_error::StorageResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The `bucket=` query parameter of `/stats`.
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsParams {
+    pub bucket: Option<String>,
+}
+
+/// Seconds in a positive duration written as a count and a unit of s, m, h or d, such as `30d`.
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let text = text.trim().to_ascii_lowercase();
+    let (i, unit) = text.char_indices().last()?;
+    let unit_seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    text[..i].parse::<i64>().ok()
+        .and_then(|count| count.checked_mul(unit_seconds))
+        .filter(|seconds| *seconds > 0)
+}
+
+/// Width of an aggregation bucket; buckets start at multiples of it since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bucket(i64);
+
+impl Bucket {
+    /// Parse the `bucket=` query parameter, such as `15m`, `1h` or `1d`; one hour when absent.
+    pub fn parse(bucket: Option<&str>) -> Result<Self, String> {
+        let bucket = bucket.unwrap_or("1h");
+        parse_duration(bucket)
+            .map(Bucket)
+            .ok_or_else(|| format!("Invalid bucket '{}'; use a count and one of s, m, h, d, such as 1h", bucket.trim()))
+    }
+
+    pub fn seconds(&self) -> i64 {
+        self.0
+    }
+
+    /// Start of the bucket holding `recorded`.
+    pub fn start(&self, recorded: i64) -> i64 {
+        recorded - recorded.rem_euclid(self.0)
+    }
+}
+
+/// Summary of the readings of one series in one bucket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SensorStats {
+    pub bucket_start: i64,
+    pub location: String,
+    pub sensor: String,
+    pub measurement: String,
+    pub units: String,
+    pub count: i64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+// Buckets sort by start time, then series, the order every backend returns
+type StatsKey = (i64, String, String, String, String);
+
+/// Fold readings into per-bucket stats in process, for backends that cannot group on the server.
+pub async fn aggregate_stream(mut rows: SensorReadingStream, bucket: Bucket) -> StorageResult<Vec<SensorStats>> {
+    let mut groups: BTreeMap<StatsKey, (i64, f64, f64, f64)> = BTreeMap::new();
+    while let Some(reading) = rows.recv().await {
+        let SensorReading { recorded, location, sensor, measurement, units, value } = reading?;
+        let (count, min, max, sum) = groups
+            .entry((bucket.start(recorded), location, sensor, measurement, units))
+            .or_insert((0, f64::INFINITY, f64::NEG_INFINITY, 0.0));
+        *count += 1;
+        *min = min.min(value);
+        *max = max.max(value);
+        *sum += value;
+    }
+    Ok(groups
+        .into_iter()
+        .map(|((bucket_start, location, sensor, measurement, units), (count, min, max, sum))| SensorStats {
+            bucket_start,
+            location,
+            sensor,
+            measurement,
+            units,
+            count,
+            min,
+            max,
+            avg: sum / count as f64,
+        })
+        .collect())
+}
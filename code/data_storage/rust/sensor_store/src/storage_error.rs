@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use std::fmt;
+
+/// Why a storage operation failed, so callers can react to the kind of failure.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageError {
+    /// The backend could not be reached or dropped the connection.
+    Connection(String),
+    /// The request was rejected before reaching the backend, such as a malformed cursor.
+    Validation(String),
+    /// Nothing is stored under the requested key.
+    NotFound(String),
+    /// The write clashes with data already stored.
+    Conflict(String),
+    /// The backend did not answer in time.
+    Timeout(String),
+    /// Any other failure reported by the backend or its driver.
+    Backend(String),
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Connection(message) => write!(f, "connection error: {}", message),
+            StorageError::Validation(message) => write!(f, "invalid request: {}", message),
+            StorageError::NotFound(message) => write!(f, "not found: {}", message),
+            StorageError::Conflict(message) => write!(f, "conflict: {}", message),
+            StorageError::Timeout(message) => write!(f, "timed out: {}", message),
+            StorageError::Backend(message) => write!(f, "backend error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025-2026 ggeoffre, LLC
+
+use crate::config::SqliteConfig;
+use crate::fanout_data_access::WritePolicy;
+use crate::health::HealthCheck;
+use crate::memory_data_access::MemoryDataAccess;
+use crate::migrations::migrate;
+use crate::sensor_data_access_trait::{DynSensorDataAccess, LogOutcome};
+use crate::sensor_data_copy::{copy_readings, CopyCheckpoint};
+use crate::sensor_query::SensorQuery;
+use crate::sensor_reading::{ReadingKey, SensorReading};
+use crate::sqlite_data_access::SqliteDataAccess;
+use std::sync::Arc;
+
+const T0: i64 = 1_756_000_000;
+
+fn reading(location: &str, recorded: i64) -> SensorReading {
+    SensorReading {
+        recorded,
+        location: location.to_string(),
+        sensor: "bmp280".to_string(),
+        measurement: "temperature".to_string(),
+        units: "C".to_string(),
+        value: 21.5,
+    }
+}
+
+#[tokio::test]
+async fn copy_resumes_after_the_checkpoint_key() {
+    let source: Arc<dyn DynSensorDataAccess> = Arc::new(MemoryDataAccess::new());
+    // Three readings share a `recorded` value and stream in arrival order, not key order
+    for (location, offset) in [("kitchen", 10), ("kitchen", 20), ("den", 20), ("attic", 20), ("kitchen", 30)] {
+        source.log_sensor_data(&reading(location, T0 + offset)).await.unwrap();
+    }
+    let destination: Arc<dyn DynSensorDataAccess> = Arc::new(MemoryDataAccess::new());
+
+    // As if an earlier run stopped after writing attic and den at T0 + 20
+    let path = std::env::temp_dir().join(format!("sensor-store-checkpoint-{}.json", std::process::id()));
+    let interrupted = CopyCheckpoint {
+        source: "source".to_string(),
+        destination: "destination".to_string(),
+        last: Some(ReadingKey { location: "den".to_string(), sensor: "bmp280".to_string(), recorded: T0 + 20 }),
+        ..Default::default()
+    };
+    std::fs::write(&path, serde_json::to_string(&interrupted).unwrap()).unwrap();
+
+    let checkpoint = copy_readings(&("source".to_string(), source), &("destination".to_string(), destination.clone()), 1, Some(&path))
+        .await
+        .unwrap();
+    assert_eq!(checkpoint.created, 2);
+    assert_eq!(checkpoint.last, Some(reading("kitchen", T0 + 30).key()));
+
+    let copied = destination.fetch_sensor_data(&SensorQuery::default()).await.unwrap().readings;
+    assert_eq!(copied, [reading("kitchen", T0 + 20), reading("kitchen", T0 + 30)]);
+    let saved: CopyCheckpoint = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved, checkpoint);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn readiness_leaves_an_unmigrated_store_untouched() {
+    let path = std::env::temp_dir().join(format!("sensor-store-readiness-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let url = format!("sqlite://{}", path.display());
+    let sqlite: Arc<dyn DynSensorDataAccess> = Arc::new(
+        SqliteDataAccess::connect(&SqliteConfig { url: url.clone(), max_connections: 1 }).await.unwrap(),
+    );
+    let health = HealthCheck::new(vec![("sqlite".to_string(), sqlite.clone())], WritePolicy::All);
+
+    let readiness = health.readiness().await;
+    assert!(!readiness.ready);
+    assert_eq!(readiness.stores[0].pending_migrations, [1, 2, 3]);
+    let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+    let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'").fetch_one(&pool).await.unwrap();
+    assert_eq!(tables, 0);
+
+    migrate(&*sqlite).await.unwrap();
+    assert!(health.readiness().await.ready);
+    // The pool opened after the unique index knows the ON CONFLICT target
+    assert_eq!(sqlite.log_sensor_data(&reading("den", T0)).await.unwrap(), LogOutcome::Created);
+    assert_eq!(
+        sqlite.log_sensor_data_batch(&[reading("den", T0), reading("den", T0 + 1)]).await.unwrap(),
+        [LogOutcome::Duplicate, LogOutcome::Created]
+    );
+    std::fs::remove_file(&path).unwrap();
+}